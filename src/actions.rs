@@ -0,0 +1,124 @@
+use std::collections::HashMap;
+
+use crate::panic;
+
+/// A deferred completion of a registered promise, returning true if it completed the promise
+pub(crate) type Action<A> = Box<dyn FnOnce(A) -> bool + Send>;
+
+/// Actions that complete registered promises when an event fires
+///
+/// Each action is keyed by an id so that a promise completed by other means can remove its
+/// action, releasing the shared promise it holds.
+pub(crate) struct Actions<A> {
+    next_id: u64,
+    entries: HashMap<u64, Action<A>>,
+}
+
+impl<A> Default for Actions<A> {
+    fn default() -> Self {
+        Self {
+            next_id: 0,
+            entries: HashMap::new(),
+        }
+    }
+}
+
+impl<A> std::fmt::Debug for Actions<A> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Actions")
+            .field("pending", &self.entries.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<A> Actions<A> {
+    /// Register an action, returning the id used to remove it
+    pub(crate) fn insert(&mut self, action: Action<A>) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.entries.insert(id, action);
+        id
+    }
+
+    /// Remove an action without running it
+    ///
+    /// The action holds a promise, so callers should drop it after releasing their lock.
+    pub(crate) fn remove(&mut self, id: u64) -> Option<Action<A>> {
+        self.entries.remove(&id)
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Remove the actions with the specified ids so that they can be run
+    pub(crate) fn take(&mut self, ids: impl IntoIterator<Item = u64>) -> Fired<A> {
+        Fired {
+            actions: ids
+                .into_iter()
+                .filter_map(|id| self.entries.remove(&id))
+                .collect(),
+        }
+    }
+
+    /// Remove every action so that they can be run
+    pub(crate) fn take_all(&mut self) -> Fired<A> {
+        Fired {
+            actions: self.entries.drain().map(|(_, action)| action).collect(),
+        }
+    }
+}
+
+/// Actions removed from a registry, run once the registry's lock has been released because
+/// they complete promises whose callbacks may re-enter it
+pub(crate) struct Fired<A> {
+    actions: Vec<Action<A>>,
+}
+
+impl<A> Fired<A> {
+    /// Run every action with a clone of `arg`, returning the number of promises completed
+    ///
+    /// Actions that lost a race with another completion are not counted. A panicking action
+    /// handed the value to its callback, so it is counted, and does not prevent the remaining
+    /// actions from running.
+    pub(crate) fn run(self, arg: A) -> usize
+    where
+        A: Clone,
+    {
+        let mut count = 0;
+        for action in self.actions {
+            let arg = arg.clone();
+            let mut completed = true;
+            panic::complete_detached(|| completed = action(arg));
+            count += usize::from(completed);
+        }
+        count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn runs_taken_actions_once() {
+        let output = Arc::new(Mutex::new(Vec::new()));
+        let mut actions = Actions::default();
+        let ids: Vec<u64> = (0..3)
+            .map(|i| {
+                let output = output.clone();
+                actions.insert(Box::new(move |x: u32| {
+                    output.lock().unwrap().push(i + x);
+                    i != 0
+                }))
+            })
+            .collect();
+
+        assert!(actions.remove(ids[1]).is_some());
+        assert_eq!(actions.take([ids[0], ids[1]]).run(10), 0);
+        assert_eq!(actions.take_all().run(20), 1);
+        assert_eq!(actions.len(), 0);
+        assert_eq!(output.lock().unwrap().as_slice(), [10, 22]);
+    }
+}
@@ -0,0 +1,181 @@
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::{DropReason, Promise};
+
+#[derive(Debug)]
+struct Slot<V> {
+    value: Option<V>,
+    closed: bool,
+}
+
+#[derive(Debug)]
+struct State<V> {
+    slot: Mutex<Slot<V>>,
+    ready: Condvar,
+}
+
+/// The completing side of a [blocking_channel], wrapped by the returned [Promise]
+#[derive(Debug)]
+pub struct BlockingSender<V> {
+    state: Arc<State<V>>,
+    on_drop: fn(DropReason) -> V,
+}
+
+/// A handle that synchronously waits for the value the paired [Promise] was completed with
+///
+/// If the promise is dropped without being completed, the handle yields the value produced
+/// by the `on_drop` function passed to [blocking_channel]. Once the handle is dropped,
+/// [Promise::try_complete] returns the value instead of delivering it.
+#[derive(Debug)]
+pub struct BlockingHandle<V> {
+    state: Arc<State<V>>,
+}
+
+/// Create a promise whose completion can be waited on synchronously
+#[track_caller]
+pub fn blocking_channel<V>(
+    on_drop: fn(DropReason) -> V,
+) -> (Promise<BlockingSender<V>, V>, BlockingHandle<V>) {
+    let state = Arc::new(State {
+        slot: Mutex::new(Slot {
+            value: None,
+            closed: false,
+        }),
+        ready: Condvar::new(),
+    });
+    let sender = BlockingSender {
+        state: state.clone(),
+        on_drop,
+    };
+    let promise = Promise::from_parts(sender, BlockingSender::send, |sender, reason| {
+        let value = (sender.on_drop)(reason);
+        let _ = sender.send(value);
+    });
+    (promise, BlockingHandle { state })
+}
+
+impl<V> BlockingSender<V> {
+    fn send(self, value: V) -> Result<(), V> {
+        {
+            let mut slot = self.state.slot.lock().unwrap();
+            if slot.closed {
+                return Err(value);
+            }
+            slot.value = Some(value);
+        }
+        self.state.ready.notify_all();
+        Ok(())
+    }
+}
+
+impl<V> BlockingHandle<V> {
+    /// Block the current thread until the promise is completed
+    pub fn wait(self) -> V {
+        let mut slot = self.state.slot.lock().unwrap();
+        loop {
+            if let Some(x) = slot.value.take() {
+                return x;
+            }
+            slot = self.state.ready.wait(slot).unwrap();
+        }
+    }
+
+    /// Block the current thread until the promise is completed or the timeout elapses
+    ///
+    /// On timeout the handle is returned so that the caller may wait again.
+    pub fn wait_timeout(self, timeout: Duration) -> Result<V, Self> {
+        let deadline = match Instant::now().checked_add(timeout) {
+            Some(x) => x,
+            // a timeout this long never elapses
+            None => return Ok(self.wait()),
+        };
+        {
+            let mut slot = self.state.slot.lock().unwrap();
+            loop {
+                if let Some(x) = slot.value.take() {
+                    return Ok(x);
+                }
+                let now = Instant::now();
+                if now >= deadline {
+                    break;
+                }
+                slot = self
+                    .state
+                    .ready
+                    .wait_timeout(slot, deadline - now)
+                    .unwrap()
+                    .0;
+            }
+        }
+        Err(self)
+    }
+
+    /// Retrieve the value if the promise has already been completed, without blocking
+    ///
+    /// If it has not been completed, the handle is returned.
+    pub fn try_get(self) -> Result<V, Self> {
+        let value = self.state.slot.lock().unwrap().value.take();
+        value.ok_or(self)
+    }
+}
+
+impl<V> Drop for BlockingHandle<V> {
+    fn drop(&mut self) {
+        self.state.slot.lock().unwrap().closed = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn on_drop(reason: DropReason) -> Result<u32, DropReason> {
+        Err(reason)
+    }
+
+    #[test]
+    fn wait_returns_completed_value() {
+        let (promise, handle) = blocking_channel(on_drop);
+        let thread = std::thread::spawn(move || promise.complete(Ok(42)));
+        assert_eq!(handle.wait(), Ok(42));
+        thread.join().unwrap();
+    }
+
+    #[test]
+    fn wait_returns_drop_value_when_promise_dropped() {
+        let (promise, handle) = blocking_channel(on_drop);
+        drop(promise);
+        assert_eq!(handle.wait(), Err(DropReason::Abandoned));
+    }
+
+    #[test]
+    fn try_get_returns_handle_until_completed() {
+        let (promise, handle) = blocking_channel(on_drop);
+        let handle = handle.try_get().unwrap_err();
+        promise.complete(Ok(1));
+        assert_eq!(handle.try_get().unwrap(), Ok(1));
+    }
+
+    #[test]
+    fn completing_after_handle_dropped_returns_value() {
+        let (promise, handle) = blocking_channel(on_drop);
+        drop(handle);
+        assert_eq!(promise.try_complete(Ok(1)), Err(Ok(1)));
+    }
+
+    #[test]
+    fn wait_timeout_returns_handle_on_timeout() {
+        let (promise, handle) = blocking_channel(on_drop);
+        let handle = handle.wait_timeout(Duration::from_millis(10)).unwrap_err();
+        promise.complete(Ok(2));
+        assert_eq!(handle.wait_timeout(Duration::from_secs(1)).unwrap(), Ok(2));
+    }
+
+    #[test]
+    fn wait_timeout_accepts_unbounded_timeout() {
+        let (promise, handle) = blocking_channel(on_drop);
+        promise.complete(Ok(3));
+        assert_eq!(handle.wait_timeout(Duration::MAX).unwrap(), Ok(3));
+    }
+}
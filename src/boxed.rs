@@ -0,0 +1,154 @@
+use crate::{DropReason, Promise, StatefulFutureType};
+
+/// An object-safe counterpart of [StatefulFutureType] that can be used as `Box<dyn DynFutureType<V>>`
+///
+/// Every [StatefulFutureType] (and therefore every [FutureType](crate::FutureType)) implements
+/// this trait via a blanket implementation. The methods are named differently from those of
+/// [StatefulFutureType] so that calls on concrete types stay unambiguous when both are in scope.
+pub trait DynFutureType<V> {
+    /// The value that will be returned if the Promise wrapping this instance is dropped without being completed
+    fn drop_value_dyn(&self, reason: DropReason) -> V;
+
+    /// Attempt to complete the future with the specified value, returning it if it could not be delivered
+    fn try_deliver_dyn(self: Box<Self>, result: V) -> Result<(), V>;
+}
+
+impl<T, V> DynFutureType<V> for T
+where
+    T: StatefulFutureType<V>,
+{
+    fn drop_value_dyn(&self, reason: DropReason) -> V {
+        self.drop_value(reason)
+    }
+
+    fn try_deliver_dyn(self: Box<Self>, result: V) -> Result<(), V> {
+        (*self).try_deliver(result)
+    }
+}
+
+trait ErasedPromise<V> {
+    fn try_complete(self: Box<Self>, result: V) -> Result<(), V>;
+
+    fn abandon(self: Box<Self>, reason: DropReason);
+}
+
+impl<T, V> ErasedPromise<V> for Promise<T, V> {
+    fn try_complete(self: Box<Self>, result: V) -> Result<(), V> {
+        Promise::try_complete(*self, result)
+    }
+
+    fn abandon(self: Box<Self>, reason: DropReason) {
+        Promise::abandon(*self, reason)
+    }
+}
+
+/// A promise whose inner type has been erased, so that promises created from different
+/// callback types sharing a value type can be stored together
+///
+/// Dropping a `BoxPromise` drops the underlying [Promise], which completes it exactly as
+/// dropping the original would.
+pub struct BoxPromise<V> {
+    inner: Box<dyn ErasedPromise<V> + Send>,
+}
+
+impl<V> std::fmt::Debug for BoxPromise<V> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BoxPromise").finish_non_exhaustive()
+    }
+}
+
+impl<V> BoxPromise<V>
+where
+    V: 'static,
+{
+    /// Wrap a boxed callback into a drop-safe promise
+    #[track_caller]
+    pub fn new(callback: Box<dyn DynFutureType<V> + Send>) -> Self {
+        Promise::from_parts(
+            callback,
+            |cb, result| cb.try_deliver_dyn(result),
+            |cb, reason| {
+                let value = cb.drop_value_dyn(reason);
+                let _ = cb.try_deliver_dyn(value);
+            },
+        )
+        .into()
+    }
+}
+
+impl<V> BoxPromise<V> {
+    /// Complete the promise, consuming it
+    pub fn complete(self, result: V) {
+        let _ = self.try_complete(result);
+    }
+
+    /// Complete the promise, consuming it and returning the value if it could not be delivered
+    pub fn try_complete(self, result: V) -> Result<(), V> {
+        self.inner.try_complete(result)
+    }
+
+    /// Complete the promise with the drop value for the specified reason, consuming it
+    pub fn abandon(self, reason: DropReason) {
+        self.inner.abandon(reason)
+    }
+}
+
+impl<T, V> From<Promise<T, V>> for BoxPromise<V>
+where
+    T: Send + 'static,
+    V: 'static,
+{
+    fn from(promise: Promise<T, V>) -> Self {
+        Self {
+            inner: Box::new(promise),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::channel;
+    use crate::testing::*;
+
+    crate::mock_future_type! {
+        struct MockRead: Result<u32, DropReason> = Err;
+    }
+
+    #[test]
+    fn stores_promises_of_different_types_together() {
+        let (mocked, mocked_recorder) = mock(Err);
+        let (async_promise, receiver) = channel(Err);
+        let (callback, boxed_recorder) = MockRead::new();
+        let boxed = BoxPromise::new(Box::new(callback));
+
+        let mut promises: Vec<BoxPromise<Result<u32, DropReason>>> =
+            vec![mocked.into(), async_promise.into(), boxed];
+        promises.pop().unwrap().abandon(DropReason::Shutdown);
+        drop(receiver);
+        assert_eq!(promises.pop().unwrap().try_complete(Ok(2)), Err(Ok(2)));
+        drop(promises);
+
+        assert_eq!(assert_dropped(&mocked_recorder), DropReason::Abandoned);
+        assert_eq!(assert_dropped(&boxed_recorder), DropReason::Shutdown);
+    }
+
+    #[test]
+    fn stateful_methods_are_unambiguous_with_both_traits_in_scope() {
+        let (callback, recorder) = MockRead::new();
+        let value = callback.drop_value(DropReason::Cancelled);
+        assert_eq!(callback.try_deliver(value), Ok(()));
+        assert_eq!(assert_dropped(&recorder), DropReason::Cancelled);
+    }
+
+    #[test]
+    fn completes_boxed_callback() {
+        let (callback, recorder) = MockRead::new();
+        BoxPromise::new(Box::new(callback)).complete(Ok(1));
+        assert_completed_with(&recorder, Ok(1));
+
+        let (callback, recorder) = MockRead::new();
+        drop(BoxPromise::new(Box::new(callback)));
+        assert_eq!(assert_dropped(&recorder), DropReason::Abandoned);
+    }
+}
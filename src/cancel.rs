@@ -0,0 +1,249 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+
+use crate::actions::{Action, Actions};
+use crate::{Promise, SharedPromise};
+
+#[derive(Debug, Default)]
+struct State {
+    cancelled: bool,
+    actions: Actions<()>,
+    next_waker: u64,
+    wakers: HashMap<u64, Waker>,
+}
+
+/// A token used to cancel in-flight operations and the promises paired with it
+///
+/// Clones of the token share the same cancellation state.
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken {
+    state: Arc<Mutex<State>>,
+}
+
+impl CancellationToken {
+    /// Create a token that has not been cancelled
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancel the token, completing every paired promise that has not yet been completed
+    pub fn cancel(&self) {
+        let (actions, wakers) = {
+            let mut state = self.state.lock().unwrap();
+            state.cancelled = true;
+            (state.actions.take_all(), std::mem::take(&mut state.wakers))
+        };
+        actions.run(());
+        for waker in wakers.into_values() {
+            waker.wake();
+        }
+    }
+
+    /// Returns true if the token has been cancelled
+    pub fn is_cancelled(&self) -> bool {
+        self.state.lock().unwrap().cancelled
+    }
+
+    /// Create a future that resolves once the token has been cancelled
+    pub fn cancelled(&self) -> WaitForCancellation {
+        WaitForCancellation {
+            token: self.clone(),
+            waker: None,
+        }
+    }
+
+    /// Register an action run on cancellation, or run it now if already cancelled
+    fn register(&self, action: Action<()>) -> Option<u64> {
+        {
+            let mut state = self.state.lock().unwrap();
+            if !state.cancelled {
+                return Some(state.actions.insert(action));
+            }
+        }
+        action(());
+        None
+    }
+
+    fn unregister(&self, id: Option<u64>) {
+        if let Some(id) = id {
+            let action = self.state.lock().unwrap().actions.remove(id);
+            drop(action);
+        }
+    }
+}
+
+/// A [Future] that resolves once a [CancellationToken] has been cancelled
+#[derive(Debug)]
+pub struct WaitForCancellation {
+    token: CancellationToken,
+    /// Id of the waker slot in the token, removed when the future is dropped
+    waker: Option<u64>,
+}
+
+impl Future for WaitForCancellation {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = &mut *self;
+        let mut state = this.token.state.lock().unwrap();
+        if state.cancelled {
+            return Poll::Ready(());
+        }
+        let id = *this.waker.get_or_insert_with(|| {
+            let id = state.next_waker;
+            state.next_waker += 1;
+            id
+        });
+        match state.wakers.get_mut(&id) {
+            Some(waker) => waker.clone_from(cx.waker()),
+            None => {
+                state.wakers.insert(id, cx.waker().clone());
+            }
+        }
+        Poll::Pending
+    }
+}
+
+impl Drop for WaitForCancellation {
+    fn drop(&mut self) {
+        if let Some(id) = self.waker {
+            let waker = self.token.state.lock().unwrap().wakers.remove(&id);
+            drop(waker);
+        }
+    }
+}
+
+/// The inner value of a promise produced by [Promise::with_cancellation]
+#[derive(Debug)]
+pub struct Cancellable<T, V> {
+    shared: SharedPromise<T, V>,
+    token: CancellationToken,
+    id: Option<u64>,
+}
+
+impl<T, V> Promise<T, V>
+where
+    T: Send + 'static,
+    V: Send + 'static,
+{
+    /// Pair this promise with a cancellation token
+    ///
+    /// Cancelling the token immediately completes this promise with `cancel_value`.
+    /// Completions arriving after cancellation are discarded and [Promise::try_complete]
+    /// returns their value.
+    #[track_caller]
+    pub fn with_cancellation(
+        self,
+        token: &CancellationToken,
+        cancel_value: V,
+    ) -> Promise<Cancellable<T, V>, V> {
+        let shared = SharedPromise::new(self);
+        let cancelled = shared.clone();
+        let id = token.register(Box::new(move |()| cancelled.try_complete(cancel_value)));
+        Promise::from_parts(
+            Cancellable {
+                shared,
+                token: token.clone(),
+                id,
+            },
+            |inner, value| {
+                inner.token.unregister(inner.id);
+                inner.shared.try_deliver(value)
+            },
+            |inner, reason| {
+                inner.token.unregister(inner.id);
+                inner.shared.try_abandon(reason);
+            },
+        )
+        .settled_when(|inner| inner.shared.is_completed())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+    use crate::DropReason;
+
+    #[test]
+    fn cancel_completes_promise_immediately() {
+        let token = CancellationToken::new();
+        let (promise, recorder) = mock(Err);
+        let promise = promise.with_cancellation(&token, Err(DropReason::Cancelled));
+        assert!(!token.is_cancelled());
+
+        token.clone().cancel();
+        assert!(token.is_cancelled());
+        assert_completed_with(&recorder, Err(DropReason::Cancelled));
+
+        // the late completion is discarded
+        assert_eq!(promise.try_complete(Ok(1)), Err(Ok(1)));
+    }
+
+    #[test]
+    fn pairing_with_cancelled_token_completes_immediately() {
+        let token = CancellationToken::new();
+        token.cancel();
+        let (promise, recorder) = mock(|_| 0u32);
+        let _promise = promise.with_cancellation(&token, 7);
+        assert_completed_with(&recorder, 7);
+    }
+
+    #[test]
+    fn completion_before_cancel_wins() {
+        let token = CancellationToken::new();
+        let (promise, recorder) = mock(Err);
+        promise
+            .with_cancellation(&token, Err(DropReason::Cancelled))
+            .complete(Ok(1));
+        assert_eq!(token.state.lock().unwrap().actions.len(), 0);
+        token.cancel();
+        assert_completed_with(&recorder, Ok(1));
+    }
+
+    #[test]
+    fn dropping_before_cancel_completes_with_drop_value() {
+        let token = CancellationToken::new();
+        let (promise, recorder) = mock(Err::<u32, _>);
+        drop(promise.with_cancellation(&token, Err(DropReason::Cancelled)));
+        token.cancel();
+        assert_eq!(assert_dropped(&recorder), DropReason::Abandoned);
+    }
+
+    #[test]
+    fn panicking_callback_does_not_stop_other_cancellations() {
+        let token = CancellationToken::new();
+        let _first = crate::from_fn(|_: Result<u32, _>| panic!("callback panicked"), Err)
+            .with_cancellation(&token, Err(DropReason::Cancelled));
+        let (second, recorder) = mock(Err::<u32, _>);
+        let _second = second.with_cancellation(&token, Err(DropReason::Cancelled));
+        token.cancel();
+        assert_completed_with(&recorder, Err(DropReason::Cancelled));
+    }
+
+    #[test]
+    fn cancelled_future_resolves_after_cancel() {
+        let token = CancellationToken::new();
+        let mut future = token.cancelled();
+        let mut cx = Context::from_waker(Waker::noop());
+        assert_eq!(Pin::new(&mut future).poll(&mut cx), Poll::Pending);
+        token.cancel();
+        assert_eq!(Pin::new(&mut future).poll(&mut cx), Poll::Ready(()));
+    }
+
+    #[test]
+    fn dropped_futures_release_their_wakers() {
+        let token = CancellationToken::new();
+        let mut cx = Context::from_waker(Waker::noop());
+        for _ in 0..3 {
+            let mut future = token.cancelled();
+            assert_eq!(Pin::new(&mut future).poll(&mut cx), Poll::Pending);
+            assert_eq!(Pin::new(&mut future).poll(&mut cx), Poll::Pending);
+            assert_eq!(token.state.lock().unwrap().wakers.len(), 1);
+        }
+        assert!(token.state.lock().unwrap().wakers.is_empty());
+    }
+}
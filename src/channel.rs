@@ -0,0 +1,150 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+
+use crate::{DropReason, Promise};
+
+#[derive(Debug)]
+struct State<V> {
+    value: Option<V>,
+    waker: Option<Waker>,
+    closed: bool,
+}
+
+/// The completing side of a [channel], wrapped by the returned [Promise]
+#[derive(Debug)]
+pub struct Sender<V> {
+    state: Arc<Mutex<State<V>>>,
+    on_drop: fn(DropReason) -> V,
+}
+
+/// A [Future] that resolves to the value the paired [Promise] was completed with
+///
+/// If the promise is dropped without being completed, the receiver resolves to the
+/// value produced by the `on_drop` function passed to [channel]. Once the receiver is
+/// dropped, [Promise::try_complete] returns the value instead of delivering it.
+#[derive(Debug)]
+pub struct Receiver<V> {
+    state: Arc<Mutex<State<V>>>,
+}
+
+/// Create a promise whose completion can be awaited from Rust
+///
+/// The receiver does not depend on any particular async runtime.
+#[track_caller]
+pub fn channel<V>(on_drop: fn(DropReason) -> V) -> (Promise<Sender<V>, V>, Receiver<V>) {
+    let state = Arc::new(Mutex::new(State {
+        value: None,
+        waker: None,
+        closed: false,
+    }));
+    let sender = Sender {
+        state: state.clone(),
+        on_drop,
+    };
+    let promise = Promise::from_parts(sender, Sender::send, |sender, reason| {
+        let value = (sender.on_drop)(reason);
+        let _ = sender.send(value);
+    });
+    (promise, Receiver { state })
+}
+
+impl<V> Sender<V> {
+    fn send(self, value: V) -> Result<(), V> {
+        let waker = {
+            let mut state = self.state.lock().unwrap();
+            if state.closed {
+                return Err(value);
+            }
+            state.value = Some(value);
+            state.waker.take()
+        };
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+        Ok(())
+    }
+}
+
+impl<V> Drop for Receiver<V> {
+    fn drop(&mut self) {
+        self.state.lock().unwrap().closed = true;
+    }
+}
+
+impl<V> Future for Receiver<V> {
+    type Output = V;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = self.state.lock().unwrap();
+        match state.value.take() {
+            Some(value) => Poll::Ready(value),
+            None => {
+                state.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::task::Wake;
+    use std::thread::Thread;
+
+    struct Unparker(Thread);
+
+    impl Wake for Unparker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let mut future = Box::pin(future);
+        let waker = Waker::from(Arc::new(Unparker(std::thread::current())));
+        let mut cx = Context::from_waker(&waker);
+        loop {
+            if let Poll::Ready(x) = future.as_mut().poll(&mut cx) {
+                return x;
+            }
+            std::thread::park();
+        }
+    }
+
+    fn on_drop(reason: DropReason) -> Result<u32, DropReason> {
+        Err(reason)
+    }
+
+    #[test]
+    fn resolves_to_completed_value() {
+        let (promise, receiver) = channel(on_drop);
+        promise.complete(Ok(42));
+        assert_eq!(block_on(receiver), Ok(42));
+    }
+
+    #[test]
+    fn resolves_to_drop_value_when_promise_dropped() {
+        let (promise, receiver) = channel(on_drop);
+        drop(promise);
+        assert_eq!(block_on(receiver), Err(DropReason::Abandoned));
+    }
+
+    #[test]
+    fn wakes_receiver_completed_from_another_thread() {
+        let (promise, receiver) = channel(on_drop);
+        let handle = std::thread::spawn(move || promise.complete(Ok(7)));
+        assert_eq!(block_on(receiver), Ok(7));
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn completing_after_receiver_dropped_returns_value() {
+        let (promise, receiver) = channel(on_drop);
+        drop(receiver);
+        assert_eq!(promise.try_complete(Ok(1)), Err(Ok(1)));
+    }
+}
@@ -0,0 +1,85 @@
+use crate::{DropReason, Promise};
+
+/// The inner value of a promise created by [from_fn] or [from_fn_or]
+pub struct FromFn<C, D> {
+    complete: C,
+    on_drop: D,
+}
+
+// closures do not implement Debug
+impl<C, D> std::fmt::Debug for FromFn<C, D> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FromFn").finish_non_exhaustive()
+    }
+}
+
+/// Create a drop-safe promise from closures, without defining a [FutureType](crate::FutureType)
+///
+/// `complete` receives the value the promise is completed with. If the promise is dropped or
+/// abandoned, `complete` receives the value produced by `on_drop` for the reason instead.
+#[track_caller]
+pub fn from_fn<V, C, D>(complete: C, on_drop: D) -> Promise<FromFn<C, D>, V>
+where
+    C: FnOnce(V),
+    D: FnOnce(DropReason) -> V,
+{
+    Promise::from_parts(
+        FromFn { complete, on_drop },
+        |inner, result| {
+            (inner.complete)(result);
+            Ok(())
+        },
+        |inner, reason| {
+            let value = (inner.on_drop)(reason);
+            (inner.complete)(value);
+        },
+    )
+}
+
+/// Like [from_fn], but completes with `drop_value` whatever the reason the promise is dropped
+#[track_caller]
+pub fn from_fn_or<V, C>(
+    complete: C,
+    drop_value: V,
+) -> Promise<FromFn<C, impl FnOnce(DropReason) -> V>, V>
+where
+    C: FnOnce(V),
+{
+    from_fn(complete, move |_| drop_value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn completes_with_value_or_drop_reason() {
+        let output = Arc::new(Mutex::new(Vec::new()));
+        let record = || {
+            let output = output.clone();
+            move |x: Result<u32, DropReason>| output.lock().unwrap().push(x)
+        };
+
+        from_fn(record(), Err).complete(Ok(1));
+        from_fn(record(), Err).abandon(DropReason::Shutdown);
+        drop(from_fn(record(), Err));
+        assert_eq!(
+            output.lock().unwrap().as_slice(),
+            [Ok(1), Err(DropReason::Shutdown), Err(DropReason::Abandoned)]
+        );
+    }
+
+    #[test]
+    fn debug_does_not_require_debug_closures() {
+        let promise = from_fn_or(|_: u32| {}, 0);
+        assert!(format!("{promise:?}").contains("FromFn"));
+    }
+
+    #[test]
+    fn completes_with_fixed_drop_value() {
+        let mut output = Vec::new();
+        drop(from_fn_or(|x| output.push(x), "dropped"));
+        assert_eq!(output.as_slice(), ["dropped"]);
+    }
+}
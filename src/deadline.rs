@@ -0,0 +1,316 @@
+use std::collections::BTreeSet;
+use std::sync::{Arc, Condvar, Mutex, OnceLock};
+use std::time::Instant;
+
+use crate::actions::{Action, Actions};
+use crate::{Promise, SharedPromise};
+
+/// Deadlines are ordered by time, then by the order in which they were scheduled
+type Key = (Instant, u64);
+
+#[derive(Debug, Default)]
+struct State {
+    deadlines: BTreeSet<Key>,
+    actions: Actions<()>,
+    stopped: bool,
+}
+
+#[derive(Debug, Default)]
+struct Shared {
+    state: Mutex<State>,
+    changed: Condvar,
+}
+
+impl Shared {
+    fn expire(&self, now: Instant) -> usize {
+        let expired = {
+            let mut state = self.state.lock().unwrap();
+            let mut expired = Vec::new();
+            while state.deadlines.first().is_some_and(|key| key.0 <= now) {
+                expired.extend(state.deadlines.pop_first().map(|key| key.1));
+            }
+            state.actions.take(expired)
+        };
+        expired.run(())
+    }
+
+    fn run(&self) {
+        let mut state = self.state.lock().unwrap();
+        loop {
+            if state.stopped {
+                return;
+            }
+            let now = Instant::now();
+            state = match state.deadlines.first().map(|key| key.0) {
+                Some(deadline) if deadline <= now => {
+                    drop(state);
+                    self.expire(now);
+                    self.state.lock().unwrap()
+                }
+                Some(deadline) => self.changed.wait_timeout(state, deadline - now).unwrap().0,
+                None => self.changed.wait(state).unwrap(),
+            };
+        }
+    }
+}
+
+/// Fires promise deadlines registered with [Promise::with_deadline_on]
+///
+/// A timer is either driven by a background thread using the system clock, or driven
+/// manually via [Timer::expire], which allows deadlines to be tested with a mock clock.
+/// Dropping a timer discards any deadlines that have not yet fired.
+#[derive(Debug)]
+pub struct Timer {
+    shared: Arc<Shared>,
+}
+
+impl Timer {
+    /// Create a timer that only fires deadlines when [Timer::expire] is called
+    pub fn manual() -> Self {
+        Self {
+            shared: Default::default(),
+        }
+    }
+
+    /// Create a timer that fires deadlines from a background thread
+    pub fn spawn() -> Self {
+        let timer = Self::manual();
+        let shared = timer.shared.clone();
+        std::thread::Builder::new()
+            .name("promise-timer".to_string())
+            .spawn(move || shared.run())
+            .expect("unable to spawn timer thread");
+        timer
+    }
+
+    /// The timer used by [Promise::with_deadline], started on first use
+    pub fn global() -> &'static Timer {
+        static GLOBAL: OnceLock<Timer> = OnceLock::new();
+        GLOBAL.get_or_init(Timer::spawn)
+    }
+
+    /// Fire every deadline at or before `now`, returning the number of promises completed
+    ///
+    /// Promises completed concurrently by another path are not counted.
+    pub fn expire(&self, now: Instant) -> usize {
+        self.shared.expire(now)
+    }
+
+    /// The earliest deadline that has not yet fired
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.shared
+            .state
+            .lock()
+            .unwrap()
+            .deadlines
+            .first()
+            .map(|key| key.0)
+    }
+
+    fn schedule(&self, deadline: Instant, action: Action<()>) -> Key {
+        let key = {
+            let mut state = self.shared.state.lock().unwrap();
+            let key = (deadline, state.actions.insert(action));
+            state.deadlines.insert(key);
+            key
+        };
+        self.shared.changed.notify_one();
+        key
+    }
+}
+
+impl Drop for Timer {
+    fn drop(&mut self) {
+        self.shared.state.lock().unwrap().stopped = true;
+        self.shared.changed.notify_one();
+    }
+}
+
+/// The inner value of a promise produced by [Promise::with_deadline]
+#[derive(Debug)]
+pub struct Deadline<T, V> {
+    shared: SharedPromise<T, V>,
+    timer: Arc<Shared>,
+    key: Key,
+}
+
+impl<T, V> Deadline<T, V> {
+    fn unregister(&self) {
+        let action = {
+            let mut state = self.timer.state.lock().unwrap();
+            state.deadlines.remove(&self.key);
+            state.actions.remove(self.key.1)
+        };
+        drop(action);
+    }
+}
+
+impl<T, V> Promise<T, V>
+where
+    T: Send + 'static,
+    V: Send + 'static,
+{
+    /// Create a promise that completes this promise with `timeout_value` if it has not been
+    /// completed by `deadline`. Completions arriving after the deadline are ignored and
+    /// [Promise::try_complete] returns their value.
+    ///
+    /// The deadline is fired by [Timer::global].
+    #[track_caller]
+    pub fn with_deadline(self, deadline: Instant, timeout_value: V) -> Promise<Deadline<T, V>, V> {
+        self.with_deadline_on(Timer::global(), deadline, timeout_value)
+    }
+
+    /// Like [Promise::with_deadline], but the deadline is fired by the specified timer
+    #[track_caller]
+    pub fn with_deadline_on(
+        self,
+        timer: &Timer,
+        deadline: Instant,
+        timeout_value: V,
+    ) -> Promise<Deadline<T, V>, V> {
+        let shared = SharedPromise::new(self);
+        let expired = shared.clone();
+        let key = timer.schedule(
+            deadline,
+            Box::new(move |()| expired.try_complete(timeout_value)),
+        );
+        Promise::from_parts(
+            Deadline {
+                shared,
+                timer: timer.shared.clone(),
+                key,
+            },
+            |inner, value| {
+                inner.unregister();
+                inner.shared.try_deliver(value)
+            },
+            |inner, reason| {
+                inner.unregister();
+                inner.shared.try_abandon(reason);
+            },
+        )
+        .settled_when(|inner| inner.shared.is_completed())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+    use crate::{blocking_channel, DropReason};
+    use std::time::Duration;
+
+    fn on_drop(reason: DropReason) -> Result<u32, String> {
+        Err(format!("{reason:?}"))
+    }
+
+    fn timeout() -> Result<u32, String> {
+        Err("timeout".to_string())
+    }
+
+    #[test]
+    fn completes_with_timeout_value_when_deadline_passes() {
+        let timer = Timer::manual();
+        let now = Instant::now();
+        let (promise, recorder) = mock(on_drop);
+        let promise = promise.with_deadline_on(&timer, now + Duration::from_secs(10), timeout());
+
+        assert_eq!(timer.expire(now + Duration::from_secs(9)), 0);
+        assert_not_yet_completed(&recorder);
+        assert_eq!(timer.next_deadline(), Some(now + Duration::from_secs(10)));
+
+        assert_eq!(timer.expire(now + Duration::from_secs(10)), 1);
+        assert_completed_with(&recorder, timeout());
+        assert_eq!(timer.next_deadline(), None);
+
+        // the late completion is ignored
+        assert_eq!(promise.try_complete(Ok(42)), Err(Ok(42)));
+    }
+
+    #[test]
+    fn completion_before_deadline_wins() {
+        let timer = Timer::manual();
+        let now = Instant::now();
+        let (promise, recorder) = mock(on_drop);
+        promise
+            .with_deadline_on(&timer, now + Duration::from_secs(1), timeout())
+            .complete(Ok(42));
+        assert_eq!(timer.next_deadline(), None);
+        assert_eq!(timer.expire(now + Duration::from_secs(1)), 0);
+        assert_completed_with(&recorder, Ok(42));
+    }
+
+    #[test]
+    fn dropping_before_deadline_completes_with_drop_value() {
+        let timer = Timer::manual();
+        let (promise, recorder) = mock(on_drop);
+        drop(promise.with_deadline_on(&timer, Instant::now(), timeout()));
+        assert_eq!(assert_dropped(&recorder), DropReason::Abandoned);
+        assert_eq!(timer.expire(Instant::now()), 0);
+    }
+
+    #[test]
+    fn fires_deadlines_in_order() {
+        let timer = Timer::manual();
+        let now = Instant::now();
+        let (first, first_recorder) = mock(on_drop);
+        let (second, second_recorder) = mock(on_drop);
+        let _second = second.with_deadline_on(&timer, now + Duration::from_secs(2), timeout());
+        let _first = first.with_deadline_on(&timer, now + Duration::from_secs(1), timeout());
+
+        assert_eq!(timer.expire(now + Duration::from_secs(1)), 1);
+        assert_completed_with(&first_recorder, timeout());
+        assert_not_yet_completed(&second_recorder);
+        assert_eq!(timer.expire(now + Duration::from_secs(2)), 1);
+        assert_completed_with(&second_recorder, timeout());
+    }
+
+    #[test]
+    fn panicking_callback_does_not_stop_other_deadlines() {
+        let timer = Timer::manual();
+        let now = Instant::now();
+        let _first = crate::from_fn(
+            |_: Result<u32, String>| panic!("callback panicked"),
+            on_drop,
+        )
+        .with_deadline_on(&timer, now, timeout());
+        let (second, recorder) = mock(on_drop);
+        let _second = second.with_deadline_on(&timer, now, timeout());
+
+        assert_eq!(timer.expire(now), 2);
+        assert_completed_with(&recorder, timeout());
+    }
+
+    #[test]
+    fn expire_counts_only_promises_it_completed() {
+        type Slot = Arc<Mutex<Option<crate::BoxPromise<Result<u32, String>>>>>;
+        let timer = Timer::manual();
+        let now = Instant::now();
+        let slots: [Slot; 2] = Default::default();
+        for (i, slot) in slots.iter().enumerate() {
+            // each callback completes the other promise, racing its deadline
+            let other = slots[1 - i].clone();
+            let promise = crate::from_fn(
+                move |_: Result<u32, String>| {
+                    if let Some(other) = other.lock().unwrap().take() {
+                        other.complete(Ok(0));
+                    }
+                },
+                on_drop,
+            );
+            *slot.lock().unwrap() = Some(promise.with_deadline_on(&timer, now, timeout()).into());
+        }
+        assert_eq!(timer.expire(now), 1);
+    }
+
+    #[test]
+    fn global_timer_fires_deadline() {
+        // completed on the timer thread, so wait for it
+        let (promise, handle) = blocking_channel(on_drop);
+        let _promise = promise.with_deadline(Instant::now() + Duration::from_millis(10), timeout());
+        assert_eq!(
+            handle.wait_timeout(Duration::from_secs(5)).unwrap(),
+            timeout()
+        );
+    }
+}
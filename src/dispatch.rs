@@ -0,0 +1,275 @@
+use std::cell::Cell;
+use std::collections::VecDeque;
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::JoinHandle;
+
+use crate::{panic, DropReason, Promise};
+
+type Job = Box<dyn FnOnce() + Send>;
+
+thread_local! {
+    /// Address of the [Shared] state of the dispatcher owning the current callback thread
+    static DISPATCHER: Cell<usize> = const { Cell::new(0) };
+}
+
+#[derive(Default)]
+struct State {
+    queue: VecDeque<Job>,
+    active: usize,
+    shutdown: bool,
+}
+
+impl std::fmt::Debug for State {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("State")
+            .field("queued", &self.queue.len())
+            .field("active", &self.active)
+            .field("shutdown", &self.shutdown)
+            .finish()
+    }
+}
+
+#[derive(Debug, Default)]
+struct Shared {
+    state: Mutex<State>,
+    work: Condvar,
+    idle: Condvar,
+}
+
+impl Shared {
+    fn address(&self) -> usize {
+        std::ptr::from_ref(self) as usize
+    }
+
+    /// Panic if called from one of the callback threads of this dispatcher
+    #[track_caller]
+    fn assert_not_on_callback_thread(&self, method: &str) {
+        if DISPATCHER.with(|x| x.get()) == self.address() {
+            panic!(
+                "Dispatcher::{method} called from one of its own callback threads would deadlock"
+            );
+        }
+    }
+
+    /// Queue a job, or run it inline if the dispatcher has shut down
+    fn execute(&self, job: Job) {
+        {
+            let mut state = self.state.lock().unwrap();
+            if !state.shutdown {
+                state.queue.push_back(job);
+                self.work.notify_one();
+                return;
+            }
+        }
+        panic::complete_detached(job);
+    }
+
+    fn run(&self) {
+        DISPATCHER.with(|x| x.set(self.address()));
+        let mut state = self.state.lock().unwrap();
+        loop {
+            if let Some(job) = state.queue.pop_front() {
+                state.active += 1;
+                drop(state);
+                panic::complete_detached(job);
+                state = self.state.lock().unwrap();
+                state.active -= 1;
+                if state.queue.is_empty() && state.active == 0 {
+                    self.idle.notify_all();
+                }
+            } else if state.shutdown {
+                return;
+            } else {
+                state = self.work.wait(state).unwrap();
+            }
+        }
+    }
+}
+
+/// Runs completions on dedicated callback threads instead of the thread completing the promise
+///
+/// Promises are routed through a dispatcher with [Promise::dispatch_on]. Once the dispatcher
+/// has shut down, completions run inline on the completing thread so they are never lost.
+#[derive(Debug)]
+pub struct Dispatcher {
+    shared: Arc<Shared>,
+    threads: Vec<JoinHandle<()>>,
+}
+
+impl Dispatcher {
+    /// Create a dispatcher running completions on `threads` callback threads
+    pub fn new(threads: usize) -> Self {
+        let shared = Arc::new(Shared::default());
+        let threads = (0..threads.max(1))
+            .map(|i| {
+                let shared = shared.clone();
+                std::thread::Builder::new()
+                    .name(format!("promise-dispatch-{i}"))
+                    .spawn(move || shared.run())
+                    .expect("unable to spawn dispatcher thread")
+            })
+            .collect();
+        Self { shared, threads }
+    }
+
+    /// Block until every queued completion has run
+    ///
+    /// # Panics
+    ///
+    /// Panics if called from a completion running on one of this dispatcher's callback threads,
+    /// which would otherwise wait for itself forever.
+    #[track_caller]
+    pub fn flush(&self) {
+        self.shared.assert_not_on_callback_thread("flush");
+        let mut state = self.shared.state.lock().unwrap();
+        while !state.queue.is_empty() || state.active > 0 {
+            state = self.shared.idle.wait(state).unwrap();
+        }
+    }
+
+    /// Shut down the dispatcher, running every queued completion and joining the callback threads
+    ///
+    /// # Panics
+    ///
+    /// Panics if called from a completion running on one of this dispatcher's callback threads,
+    /// which cannot join itself. The dispatcher is still shut down.
+    #[track_caller]
+    pub fn join(mut self) {
+        self.shutdown();
+        self.shared.assert_not_on_callback_thread("join");
+        for thread in self.threads.drain(..) {
+            let _ = thread.join();
+        }
+    }
+
+    fn shutdown(&self) {
+        self.shared.state.lock().unwrap().shutdown = true;
+        self.shared.work.notify_all();
+    }
+}
+
+impl Drop for Dispatcher {
+    /// Shut down the dispatcher without waiting for queued completions to run
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+/// The inner value of a promise produced by [Promise::dispatch_on]
+#[derive(Debug)]
+pub struct Dispatched<T, V> {
+    promise: Promise<T, V>,
+    shared: Arc<Shared>,
+}
+
+impl<T, V> Promise<T, V>
+where
+    T: Send + 'static,
+    V: Send + 'static,
+{
+    /// Create a promise whose completions, including completion on drop, run on the
+    /// callback threads of `dispatcher`
+    #[track_caller]
+    pub fn dispatch_on(self, dispatcher: &Dispatcher) -> Promise<Dispatched<T, V>, V> {
+        Promise::from_parts(
+            Dispatched {
+                promise: self,
+                shared: dispatcher.shared.clone(),
+            },
+            |inner, value| {
+                let promise = inner.promise;
+                inner
+                    .shared
+                    .execute(Box::new(move || promise.complete(value)));
+                Ok(())
+            },
+            |inner, reason: DropReason| {
+                let promise = inner.promise;
+                inner
+                    .shared
+                    .execute(Box::new(move || promise.abandon(reason)));
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+
+    fn on_drop(reason: DropReason) -> (Result<u32, DropReason>, Option<String>) {
+        (Err(reason), thread_name())
+    }
+
+    fn thread_name() -> Option<String> {
+        std::thread::current().name().map(String::from)
+    }
+
+    #[test]
+    fn completes_on_dispatcher_thread() {
+        let dispatcher = Dispatcher::new(1);
+        let (promise, recorder) = mock(on_drop);
+        promise
+            .contramap(|x| (x, thread_name()))
+            .dispatch_on(&dispatcher)
+            .complete(Ok(1));
+        dispatcher.flush();
+        assert_completed_with(&recorder, (Ok(1), Some("promise-dispatch-0".to_string())));
+    }
+
+    #[test]
+    fn drop_completion_runs_on_dispatcher_thread() {
+        let dispatcher = Dispatcher::new(2);
+        let (promise, recorder) = mock(on_drop);
+        drop(promise.dispatch_on(&dispatcher));
+        dispatcher.flush();
+        assert_eq!(assert_dropped(&recorder), DropReason::Abandoned);
+        let (_, thread) = expect_exactly_once(&recorder);
+        assert!(thread.unwrap().starts_with("promise-dispatch-"));
+    }
+
+    #[test]
+    fn flush_waits_for_queued_completions() {
+        let dispatcher = Dispatcher::new(1);
+        let (promise, recorder) = mock(on_drop);
+        promise.dispatch_on(&dispatcher).complete((Ok(1), None));
+        dispatcher.flush();
+        assert_completed_with(&recorder, (Ok(1), None));
+    }
+
+    #[test]
+    fn flush_panics_on_own_callback_thread() {
+        let dispatcher = Arc::new(Dispatcher::new(1));
+        let (tx, rx) = std::sync::mpsc::channel();
+        let inner = dispatcher.clone();
+        crate::from_fn_or(
+            move |_: u32| {
+                let result =
+                    std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| inner.flush()));
+                tx.send(result.is_err()).unwrap();
+            },
+            0,
+        )
+        .dispatch_on(&dispatcher)
+        .complete(1);
+        assert!(rx.recv().unwrap());
+        dispatcher.flush();
+    }
+
+    #[test]
+    fn join_runs_queued_completions_then_completes_inline() {
+        let dispatcher = Dispatcher::new(1);
+        let (first, first_recorder) = mock(on_drop);
+        let (second, second_recorder) = mock(on_drop);
+        first.dispatch_on(&dispatcher).complete((Ok(1), None));
+        let second = second
+            .contramap(|x| (x, thread_name()))
+            .dispatch_on(&dispatcher);
+        dispatcher.join();
+        assert_completed_with(&first_recorder, (Ok(1), None));
+
+        second.complete(Ok(2));
+        assert_completed_with(&second_recorder, (Ok(2), thread_name()));
+    }
+}
@@ -0,0 +1,134 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use crate::{DropReason, Promise};
+
+/// A future that completes a [Promise] with the output of another future
+///
+/// Created by [Promise::drive]. If it is dropped before the inner future resolves, for
+/// example because its task was aborted or its runtime shut down, the promise is abandoned
+/// with [DropReason::Cancelled].
+#[derive(Debug)]
+pub struct Drive<F, T, V> {
+    future: Pin<Box<F>>,
+    promise: Option<Promise<T, V>>,
+}
+
+impl<T, V> Promise<T, V> {
+    /// Create a future that resolves `future` and completes the promise with its output
+    ///
+    /// The returned future does not depend on any particular runtime.
+    pub fn drive<F>(self, future: F) -> Drive<F, T, V>
+    where
+        F: Future<Output = V>,
+    {
+        Drive {
+            future: Box::pin(future),
+            promise: Some(self),
+        }
+    }
+}
+
+// the future is boxed and the promise is never pinned
+impl<F, T, V> Unpin for Drive<F, T, V> {}
+
+impl<F, T, V> Future for Drive<F, T, V>
+where
+    F: Future<Output = V>,
+{
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match self.future.as_mut().poll(cx) {
+            Poll::Pending => Poll::Pending,
+            Poll::Ready(value) => {
+                if let Some(promise) = self.promise.take() {
+                    promise.complete(value);
+                }
+                Poll::Ready(())
+            }
+        }
+    }
+}
+
+impl<F, T, V> Drop for Drive<F, T, V> {
+    fn drop(&mut self) {
+        if let Some(promise) = self.promise.take() {
+            if std::thread::panicking() {
+                // let the promise report the panic
+                drop(promise);
+            } else {
+                promise.abandon(DropReason::Cancelled);
+            }
+        }
+    }
+}
+
+/// Spawn `future` on the current tokio runtime and complete `promise` with its output
+///
+/// Aborting the returned handle or shutting down the runtime abandons the promise with
+/// [DropReason::Cancelled].
+#[cfg(feature = "tokio")]
+pub fn spawn_with_promise<F, T, V>(future: F, promise: Promise<T, V>) -> tokio::task::JoinHandle<()>
+where
+    F: Future<Output = V> + Send + 'static,
+    T: Send + 'static,
+    V: Send + 'static,
+{
+    tokio::spawn(promise.drive(future))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+    use std::task::Waker;
+
+    #[test]
+    fn completes_when_future_resolves() {
+        let (promise, recorder) = mock(Err);
+        let mut drive = promise.drive(async { Ok(7) });
+        let mut cx = Context::from_waker(Waker::noop());
+        assert_eq!(Pin::new(&mut drive).poll(&mut cx), Poll::Ready(()));
+        assert_completed_with(&recorder, Ok(7));
+    }
+
+    #[test]
+    fn cancelled_when_dropped_before_resolving() {
+        let (promise, recorder) = mock(Err::<u32, _>);
+        let mut drive = promise.drive(std::future::pending());
+        let mut cx = Context::from_waker(Waker::noop());
+        assert_eq!(Pin::new(&mut drive).poll(&mut cx), Poll::Pending);
+        assert_not_yet_completed(&recorder);
+        drop(drive);
+        assert_eq!(assert_dropped(&recorder), DropReason::Cancelled);
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn spawned_task_completes_or_is_cancelled() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+
+        let (promise, recorder) = mock(Err);
+        runtime
+            .block_on(async { spawn_with_promise(async { Ok(1) }, promise).await })
+            .unwrap();
+        assert_completed_with(&recorder, Ok(1));
+
+        let (promise, recorder) = mock(Err::<u32, _>);
+        runtime.block_on(async {
+            let task = spawn_with_promise(std::future::pending(), promise);
+            task.abort();
+            assert!(task.await.unwrap_err().is_cancelled());
+        });
+        assert_eq!(assert_dropped(&recorder), DropReason::Cancelled);
+
+        let (promise, recorder) = mock(Err::<u32, _>);
+        runtime.block_on(async { drop(spawn_with_promise(std::future::pending(), promise)) });
+        drop(runtime);
+        assert_eq!(assert_dropped(&recorder), DropReason::Cancelled);
+    }
+}
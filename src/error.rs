@@ -0,0 +1,153 @@
+use crate::{DropReason, FutureType};
+
+/// Standard errors reported by promises that complete without a value
+///
+/// Library error types implement `From<PromiseError>` to get drop values for free, either via
+/// [WithPromiseError] or by passing [drop_error] wherever an `on_drop` function is expected.
+/// Timeouts, cancellation and shutdown report through the same type by completing with
+/// [PromiseError::TimedOut], [PromiseError::Cancelled] or [PromiseError::Shutdown], e.g. as
+/// the value passed to [Promise::with_deadline](crate::Promise::with_deadline),
+/// [Promise::with_cancellation](crate::Promise::with_cancellation) or
+/// [PromiseScope::shutdown](crate::PromiseScope::shutdown).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PromiseError {
+    /// The promise was dropped without being completed
+    Dropped,
+    /// The promise was cancelled
+    Cancelled,
+    /// The promise was not completed before its deadline
+    TimedOut,
+    /// The promise was abandoned because the runtime is shutting down
+    Shutdown,
+    /// The promise was dropped while the thread was unwinding from a panic
+    Panicked,
+}
+
+impl From<DropReason> for PromiseError {
+    fn from(reason: DropReason) -> Self {
+        match reason {
+            DropReason::Panicking => PromiseError::Panicked,
+            DropReason::Cancelled => PromiseError::Cancelled,
+            DropReason::Shutdown => PromiseError::Shutdown,
+            DropReason::Abandoned => PromiseError::Dropped,
+        }
+    }
+}
+
+impl std::fmt::Display for PromiseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PromiseError::Dropped => f.write_str("promise dropped without being completed"),
+            PromiseError::Cancelled => f.write_str("promise cancelled"),
+            PromiseError::TimedOut => f.write_str("promise timed out"),
+            PromiseError::Shutdown => f.write_str("promise abandoned during shutdown"),
+            PromiseError::Panicked => f.write_str("promise dropped during a panic"),
+        }
+    }
+}
+
+impl std::error::Error for PromiseError {}
+
+/// Drop value for promises of `Result<X, E>`, usable as a `fn(DropReason) -> Result<X, E>`
+pub fn drop_error<X, E>(reason: DropReason) -> Result<X, E>
+where
+    E: From<PromiseError>,
+{
+    Err(PromiseError::from(reason).into())
+}
+
+/// Future types completed with a `Result` whose error can represent a [PromiseError]
+///
+/// Wrap an implementation in [WithPromiseError] to get a [FutureType] whose drop value is
+/// derived from the [DropReason] via [drop_error].
+pub trait ResultFutureType<X, E> {
+    /// Complete the future with the specified result
+    fn complete_result(self, result: Result<X, E>);
+}
+
+/// Adapter turning a [ResultFutureType] into a [FutureType] that completes with a
+/// [PromiseError] when dropped
+#[derive(Debug)]
+pub struct WithPromiseError<T>(pub T);
+
+impl<T, X, E> FutureType<Result<X, E>> for WithPromiseError<T>
+where
+    T: ResultFutureType<X, E>,
+    E: From<PromiseError>,
+{
+    fn on_drop() -> Result<X, E> {
+        drop_error(DropReason::Abandoned)
+    }
+
+    fn on_drop_with_reason(reason: DropReason) -> Result<X, E> {
+        drop_error(reason)
+    }
+
+    fn complete(self, result: Result<X, E>) {
+        self.0.complete_result(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+    use crate::{wrap, CancellationToken};
+
+    #[derive(Clone, Debug, PartialEq)]
+    enum Error {
+        Promise(PromiseError),
+        Io,
+    }
+
+    impl From<PromiseError> for Error {
+        fn from(err: PromiseError) -> Self {
+            Error::Promise(err)
+        }
+    }
+
+    struct Callback<'a> {
+        vec: &'a mut Vec<Result<u32, Error>>,
+    }
+
+    impl<'a> ResultFutureType<u32, Error> for Callback<'a> {
+        fn complete_result(self, result: Result<u32, Error>) {
+            self.vec.push(result);
+        }
+    }
+
+    #[test]
+    fn result_future_types_get_drop_values() {
+        let mut output = Vec::new();
+        wrap(WithPromiseError(Callback { vec: &mut output })).complete(Err(Error::Io));
+        wrap(WithPromiseError(Callback { vec: &mut output })).abandon(DropReason::Shutdown);
+        drop(wrap(WithPromiseError(Callback { vec: &mut output })));
+        assert_eq!(
+            output,
+            [
+                Err(Error::Io),
+                Err(Error::Promise(PromiseError::Shutdown)),
+                Err(Error::Promise(PromiseError::Dropped))
+            ]
+        );
+    }
+
+    #[test]
+    fn drop_error_maps_drop_reason() {
+        let (promise, recorder) = mock(drop_error::<u32, Error>);
+        promise.abandon(DropReason::Panicking);
+        assert_eq!(
+            expect_exactly_once(&recorder),
+            Err(Error::Promise(PromiseError::Panicked))
+        );
+    }
+
+    #[test]
+    fn cancellation_reports_through_promise_error() {
+        let token = CancellationToken::new();
+        let (promise, recorder) = mock(drop_error::<u32, Error>);
+        let _promise = promise.with_cancellation(&token, Err(PromiseError::Cancelled.into()));
+        token.cancel();
+        assert_completed_with(&recorder, Err(Error::Promise(PromiseError::Cancelled)));
+    }
+}
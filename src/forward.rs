@@ -0,0 +1,297 @@
+use std::sync::mpsc::{sync_channel, SyncSender};
+
+use crate::{StreamFutureType, StreamPromise};
+
+/// An event produced by a source driving a [StreamPromise]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum StreamEvent<I, V> {
+    /// An item to deliver
+    Item(I),
+    /// The terminal value, ending the stream
+    Finish(V),
+}
+
+impl<T, I, V> StreamPromise<T, I, V>
+where
+    T: StreamFutureType<I, V>,
+{
+    /// Forward events from a producer, such as an iterator or a
+    /// [Receiver](std::sync::mpsc::Receiver), until it produces a terminal value
+    ///
+    /// If the producer ends without a terminal value, the drop value is delivered.
+    pub fn forward<E>(mut self, events: E)
+    where
+        E: IntoIterator<Item = StreamEvent<I, V>>,
+    {
+        for event in events {
+            match event {
+                StreamEvent::Item(item) => self.next(item),
+                StreamEvent::Finish(terminal) => return self.finish(terminal),
+            }
+        }
+    }
+
+    /// Forward every item, then deliver `terminal` once the items are exhausted
+    pub fn forward_all<E>(mut self, items: E, terminal: V)
+    where
+        E: IntoIterator<Item = I>,
+    {
+        for item in items {
+            self.next(item);
+        }
+        self.finish(terminal);
+    }
+}
+
+/// The producing side of [StreamPromise::spawn_forwarder]
+///
+/// Dropping the sender without calling [StreamSender::finish] delivers the drop value.
+#[derive(Debug)]
+pub struct StreamSender<I, V> {
+    events: SyncSender<StreamEvent<I, V>>,
+}
+
+impl<I, V> StreamSender<I, V> {
+    /// Send an item, blocking while the forwarding queue is full
+    ///
+    /// Returns the item if it can no longer be forwarded.
+    pub fn send(&self, item: I) -> Result<(), I> {
+        self.events
+            .send(StreamEvent::Item(item))
+            .map_err(|err| match err.0 {
+                StreamEvent::Item(item) => item,
+                StreamEvent::Finish(_) => unreachable!("only items are sent"),
+            })
+    }
+
+    /// Send the terminal value, ending the stream
+    pub fn finish(self, terminal: V) {
+        let _ = self.events.send(StreamEvent::Finish(terminal));
+    }
+}
+
+impl<T, I, V> StreamPromise<T, I, V>
+where
+    T: StreamFutureType<I, V> + Send + 'static,
+    I: Send + 'static,
+    V: Send + 'static,
+{
+    /// Forward events from a dedicated thread, returning the sender used to produce them
+    ///
+    /// At most `bound` events are queued, after which [StreamSender::send] blocks, providing
+    /// backpressure to the producer.
+    pub fn spawn_forwarder(self, bound: usize) -> StreamSender<I, V> {
+        let (tx, rx) = sync_channel(bound);
+        std::thread::Builder::new()
+            .name("promise-stream".to_string())
+            .spawn(move || self.forward(rx))
+            .expect("unable to spawn stream forwarding thread");
+        StreamSender { events: tx }
+    }
+}
+
+#[cfg(feature = "futures-core")]
+mod stream {
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+
+    use futures_core::Stream;
+
+    use super::StreamEvent;
+    use crate::{StreamFutureType, StreamPromise};
+
+    /// A future that forwards events from a [Stream] to a [StreamPromise]
+    ///
+    /// If the stream ends without a terminal value, or the future is dropped before the
+    /// stream ends, the drop value is delivered.
+    #[derive(Debug)]
+    pub struct ForwardStream<S, T, I, V> {
+        stream: S,
+        promise: Option<StreamPromise<T, I, V>>,
+    }
+
+    impl<T, I, V> StreamPromise<T, I, V>
+    where
+        T: StreamFutureType<I, V>,
+    {
+        /// Create a future that forwards events from `stream` until it produces a terminal value
+        pub fn forward_stream<S>(self, stream: S) -> ForwardStream<S, T, I, V>
+        where
+            S: Stream<Item = StreamEvent<I, V>> + Unpin,
+        {
+            ForwardStream {
+                stream,
+                promise: Some(self),
+            }
+        }
+    }
+
+    // the promise is never pinned, only the stream
+    impl<S, T, I, V> Unpin for ForwardStream<S, T, I, V> where S: Unpin {}
+
+    impl<S, T, I, V> Future for ForwardStream<S, T, I, V>
+    where
+        S: Stream<Item = StreamEvent<I, V>> + Unpin,
+        T: StreamFutureType<I, V>,
+    {
+        type Output = ();
+
+        fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+            loop {
+                match Pin::new(&mut self.stream).poll_next(cx) {
+                    Poll::Pending => return Poll::Pending,
+                    Poll::Ready(Some(StreamEvent::Item(item))) => {
+                        if let Some(promise) = self.promise.as_mut() {
+                            promise.next(item);
+                        }
+                    }
+                    Poll::Ready(Some(StreamEvent::Finish(terminal))) => {
+                        if let Some(promise) = self.promise.take() {
+                            promise.finish(terminal);
+                        }
+                        return Poll::Ready(());
+                    }
+                    Poll::Ready(None) => {
+                        self.promise.take();
+                        return Poll::Ready(());
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(feature = "futures-core")]
+pub use stream::ForwardStream;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{wrap_stream, DropReason};
+    use std::sync::{Arc, Mutex};
+
+    type Events = Arc<Mutex<Vec<Result<u32, Result<(), DropReason>>>>>;
+
+    struct Collect {
+        events: Events,
+    }
+
+    impl StreamFutureType<u32, Result<(), DropReason>> for Collect {
+        fn drop_value(&self, reason: DropReason) -> Result<(), DropReason> {
+            Err(reason)
+        }
+
+        fn next(&mut self, item: u32) {
+            self.events.lock().unwrap().push(Ok(item));
+        }
+
+        fn finish(self, terminal: Result<(), DropReason>) {
+            self.events.lock().unwrap().push(Err(terminal));
+        }
+    }
+
+    fn collect() -> (StreamPromise<Collect, u32, Result<(), DropReason>>, Events) {
+        let events = Events::default();
+        let stream = wrap_stream(Collect {
+            events: events.clone(),
+        });
+        (stream, events)
+    }
+
+    #[test]
+    fn forward_all_delivers_items_then_terminal() {
+        let (stream, events) = collect();
+        stream.forward_all(1..=2, Ok(()));
+        assert_eq!(
+            events.lock().unwrap().as_slice(),
+            [Ok(1), Ok(2), Err(Ok(()))]
+        );
+    }
+
+    #[test]
+    fn forward_stops_at_terminal_event() {
+        let (stream, events) = collect();
+        stream.forward([
+            StreamEvent::Item(1),
+            StreamEvent::Finish(Ok(())),
+            StreamEvent::Item(2),
+        ]);
+        assert_eq!(events.lock().unwrap().as_slice(), [Ok(1), Err(Ok(()))]);
+    }
+
+    #[test]
+    fn forward_delivers_drop_value_when_producer_disconnects() {
+        let (stream, events) = collect();
+        let (tx, rx) = std::sync::mpsc::channel();
+        tx.send(StreamEvent::Item(1)).unwrap();
+        drop(tx);
+        stream.forward(rx);
+        assert_eq!(
+            events.lock().unwrap().as_slice(),
+            [Ok(1), Err(Err(DropReason::Abandoned))]
+        );
+    }
+
+    fn wait_for_terminal(events: &Events) -> Vec<Result<u32, Result<(), DropReason>>> {
+        loop {
+            {
+                let events = events.lock().unwrap();
+                if events.last().is_some_and(|x| x.is_err()) {
+                    return events.clone();
+                }
+            }
+            std::thread::yield_now();
+        }
+    }
+
+    #[test]
+    fn spawned_forwarder_delivers_events() {
+        let (stream, events) = collect();
+        let sender = stream.spawn_forwarder(1);
+        for i in 0..10 {
+            sender.send(i).unwrap();
+        }
+        sender.finish(Ok(()));
+        let events = wait_for_terminal(&events);
+        assert_eq!(events.len(), 11);
+        assert_eq!(events.last(), Some(&Err(Ok(()))));
+    }
+
+    #[test]
+    fn dropped_stream_sender_delivers_drop_value() {
+        let (stream, events) = collect();
+        drop(stream.spawn_forwarder(0));
+        assert_eq!(
+            wait_for_terminal(&events),
+            [Err(Err(DropReason::Abandoned))]
+        );
+    }
+
+    #[cfg(feature = "futures-core")]
+    #[test]
+    fn forward_stream_delivers_events_until_stream_ends() {
+        use std::future::Future;
+        use std::pin::Pin;
+        use std::task::{Context, Poll, Waker};
+
+        struct Iter<E>(E);
+
+        impl<E: Iterator + Unpin> futures_core::Stream for Iter<E> {
+            type Item = E::Item;
+
+            fn poll_next(mut self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Option<E::Item>> {
+                Poll::Ready(self.0.next())
+            }
+        }
+
+        let (stream, events) = collect();
+        let mut future = stream.forward_stream(Iter([StreamEvent::Item(1)].into_iter()));
+        let mut cx = Context::from_waker(Waker::noop());
+        assert_eq!(Pin::new(&mut future).poll(&mut cx), Poll::Ready(()));
+        assert_eq!(
+            events.lock().unwrap().as_slice(),
+            [Ok(1), Err(Err(DropReason::Abandoned))]
+        );
+    }
+}
@@ -0,0 +1,172 @@
+use std::panic::Location;
+use std::sync::{Arc, Mutex};
+
+use crate::{DropReason, Promise};
+
+/// What to do when a child promise produced by [join_all] is dropped without being completed
+#[derive(Debug)]
+pub enum JoinPolicy<V> {
+    /// Fill the child's slot with the value produced by the function
+    Fill(fn(DropReason) -> V),
+    /// Complete the parent promise with its drop value for the child's drop reason
+    FailParent,
+}
+
+impl<V> Clone for JoinPolicy<V> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<V> Copy for JoinPolicy<V> {}
+
+#[derive(Debug)]
+struct State<T, V> {
+    slots: Vec<Option<V>>,
+    remaining: usize,
+    parent: Option<Promise<T, Vec<V>>>,
+}
+
+/// The inner value of a child promise produced by [join_all]
+#[derive(Debug)]
+pub struct JoinChild<T, V> {
+    state: Arc<Mutex<State<T, V>>>,
+    index: usize,
+    policy: JoinPolicy<V>,
+}
+
+/// Split a promise into `count` child promises
+///
+/// The parent is completed with the values of the children, in order, once every child has
+/// been completed. A child that is dropped is handled according to `policy`.
+#[track_caller]
+pub fn join_all<T, V>(
+    parent: Promise<T, Vec<V>>,
+    count: usize,
+    policy: JoinPolicy<V>,
+) -> Vec<Promise<JoinChild<T, V>, V>> {
+    if count == 0 {
+        parent.complete(Vec::new());
+        return Vec::new();
+    }
+    let location = Location::caller();
+    let state = Arc::new(Mutex::new(State {
+        slots: (0..count).map(|_| None).collect(),
+        remaining: count,
+        parent: Some(parent),
+    }));
+    (0..count)
+        .map(|index| {
+            Promise::from_parts_at(
+                JoinChild {
+                    state: state.clone(),
+                    index,
+                    policy,
+                },
+                JoinChild::complete,
+                JoinChild::abandon,
+                location,
+            )
+        })
+        .collect()
+}
+
+impl<T, V> JoinChild<T, V> {
+    fn complete(self, value: V) -> Result<(), V> {
+        let (parent, values) = {
+            let mut state = self.state.lock().unwrap();
+            if state.parent.is_none() {
+                // the parent already failed
+                return Err(value);
+            }
+            state.slots[self.index] = Some(value);
+            state.remaining -= 1;
+            if state.remaining > 0 {
+                return Ok(());
+            }
+            let values = state.slots.drain(..).flatten().collect();
+            (state.parent.take(), values)
+        };
+        if let Some(parent) = parent {
+            parent.complete(values);
+        }
+        Ok(())
+    }
+
+    fn abandon(self, reason: DropReason) {
+        match self.policy {
+            JoinPolicy::Fill(fill) => {
+                let _ = self.complete(fill(reason));
+            }
+            JoinPolicy::FailParent => {
+                let parent = self.state.lock().unwrap().parent.take();
+                if let Some(parent) = parent {
+                    parent.abandon(reason);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+
+    fn fill(_: DropReason) -> u32 {
+        0
+    }
+
+    #[test]
+    fn completes_parent_when_every_child_completes() {
+        let (promise, recorder) = mock(Err);
+        let mut children = join_all(promise.contramap(Ok), 3, JoinPolicy::Fill(fill));
+        let third = children.pop().unwrap();
+        third.complete(3);
+        assert_not_yet_completed(&recorder);
+        for (i, child) in children.into_iter().enumerate() {
+            child.complete(i as u32 + 1);
+        }
+        assert_completed_with(&recorder, Ok(vec![1, 2, 3]));
+    }
+
+    #[test]
+    fn fill_policy_uses_drop_value_for_dropped_child() {
+        let (promise, recorder) = mock(Err);
+        let mut children = join_all(promise.contramap(Ok), 2, JoinPolicy::Fill(fill));
+        children.remove(0).complete(1);
+        drop(children);
+        assert_completed_with(&recorder, Ok(vec![1, 0]));
+    }
+
+    #[test]
+    fn fail_policy_fails_parent_when_child_dropped() {
+        let (promise, recorder) = mock(Err::<Vec<u32>, _>);
+        let mut children = join_all(promise.contramap(Ok), 2, JoinPolicy::FailParent);
+        let first = children.remove(0);
+        drop(children);
+        assert_eq!(assert_dropped(&recorder), DropReason::Abandoned);
+        assert_eq!(first.try_complete(1), Err(1));
+    }
+
+    #[cfg(feature = "registry")]
+    #[test]
+    fn children_record_caller_as_creation_site() {
+        let (promise, _recorder) = mock(Err);
+        let _children = join_all(promise.contramap(Ok), 2, JoinPolicy::Fill(fill));
+        let line = line!() - 1;
+        let children: Vec<_> = crate::registry::outstanding()
+            .into_iter()
+            .filter(|x| x.type_name.contains("JoinChild"))
+            .filter(|x| x.location.file() == file!() && x.location.line() == line)
+            .collect();
+        assert_eq!(children.len(), 2);
+    }
+
+    #[test]
+    fn no_children_completes_parent_immediately() {
+        let (promise, recorder) = mock(Err::<Vec<u32>, _>);
+        assert!(join_all(promise.contramap(Ok), 0, JoinPolicy::FailParent).is_empty());
+        assert_completed_with(&recorder, Ok(vec![]));
+    }
+}
@@ -3,15 +3,12 @@
     dead_code,
     arithmetic_overflow,
     invalid_type_param_default,
-    missing_fragment_specifier,
     mutable_transmutes,
     no_mangle_const_items,
     overflowing_literals,
     patterns_in_fns_without_body,
     pub_use_of_private_extern_crate,
     unknown_crate_types,
-    order_dependent_trait_objects,
-    illegal_floating_point_literal_pattern,
     improper_ctypes,
     late_bound_lifetime_arguments,
     non_camel_case_types,
@@ -19,7 +16,6 @@
     non_snake_case,
     non_upper_case_globals,
     no_mangle_generic_items,
-    private_in_public,
     stable_features,
     type_alias_bounds,
     tyvar_behind_raw_pointer,
@@ -39,66 +35,302 @@
 #![forbid(
     unsafe_code,
     rustdoc::broken_intra_doc_links,
-    unaligned_references,
     while_true,
     bare_trait_objects
 )]
 
+mod actions;
+mod blocking;
+mod boxed;
+mod cancel;
+mod channel;
+mod closure;
+mod deadline;
+mod dispatch;
+mod drive;
+mod error;
+mod forward;
+mod join;
+mod map;
+mod panic;
+mod pending;
+mod race;
+mod reentrancy;
+#[cfg(feature = "registry")]
+pub mod registry;
+mod scope;
+mod shared;
+mod stream;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+#[cfg(feature = "tracing")]
+mod trace;
+
+pub use blocking::{blocking_channel, BlockingHandle, BlockingSender};
+pub use boxed::{BoxPromise, DynFutureType};
+pub use cancel::{Cancellable, CancellationToken, WaitForCancellation};
+pub use channel::{channel, Receiver, Sender};
+pub use closure::{from_fn, from_fn_or, FromFn};
+pub use deadline::{Deadline, Timer};
+pub use dispatch::{Dispatched, Dispatcher};
+#[cfg(feature = "tokio")]
+pub use drive::spawn_with_promise;
+pub use drive::Drive;
+pub use error::{drop_error, PromiseError, ResultFutureType, WithPromiseError};
+#[cfg(feature = "futures-core")]
+pub use forward::ForwardStream;
+pub use forward::{StreamEvent, StreamSender};
+pub use join::{join_all, JoinChild, JoinPolicy};
+pub use map::Contramap;
+pub use panic::{clear_panic_hook, set_panic_hook};
+pub use pending::{CompleteError, PendingId, PendingMap};
+pub use race::{any, race, AnyChild, AnyResult, RaceChild};
+pub use reentrancy::{in_callback, not_in_callback};
+pub use scope::{PromiseScope, Scoped};
+pub use shared::SharedPromise;
+pub use stream::{wrap_stream, StreamFutureType, StreamPromise};
+
+/// The reason a Promise is being completed without a value
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DropReason {
+    /// The promise was dropped while the thread was unwinding from a panic
+    Panicking,
+    /// The promise was explicitly cancelled
+    Cancelled,
+    /// The promise was abandoned because the runtime is shutting down
+    Shutdown,
+    /// The promise was dropped without being completed
+    Abandoned,
+}
+
 /// Types convertible to a Promise must implement this type
 pub trait FutureType<V> {
     /// The value that will be returned if a Promise of this type is dropped without being completed
     fn on_drop() -> V;
 
+    /// The value that will be returned if a Promise of this type is completed without a value for
+    /// the specified reason. Defaults to [FutureType::on_drop].
+    fn on_drop_with_reason(reason: DropReason) -> V {
+        let _ = reason;
+        Self::on_drop()
+    }
+
     /// Complete the future with the specified value
     fn complete(self, result: V);
+
+    /// Attempt to complete the future with the specified value, returning it if it could not be
+    /// delivered, e.g. because the foreign callback was already destroyed.
+    /// Defaults to [FutureType::complete], which always succeeds.
+    fn try_complete(self, result: V) -> Result<(), V>
+    where
+        Self: Sized,
+    {
+        self.complete(result);
+        Ok(())
+    }
 }
 
-/// A Promise is a type that is guaranteed to complete its underlying FutureType,
-/// even if it is dropped.
-#[derive(Debug)]
-pub struct Promise<T, V>
+/// Like [FutureType], but the drop value is produced from the instance itself.
+///
+/// This allows the value returned on drop to carry per-promise context, e.g. a request id.
+/// Every [FutureType] is also a [StatefulFutureType] via a blanket implementation.
+pub trait StatefulFutureType<V> {
+    /// The value that will be returned if the Promise wrapping this instance is dropped without being completed
+    fn drop_value(&self, reason: DropReason) -> V;
+
+    /// Complete the future with the specified value
+    fn deliver(self, result: V);
+
+    /// Attempt to complete the future with the specified value, returning it if it could not be
+    /// delivered. Defaults to [StatefulFutureType::deliver], which always succeeds.
+    fn try_deliver(self, result: V) -> Result<(), V>
+    where
+        Self: Sized,
+    {
+        self.deliver(result);
+        Ok(())
+    }
+}
+
+impl<T, V> StatefulFutureType<V> for T
 where
     T: FutureType<V>,
 {
+    fn drop_value(&self, reason: DropReason) -> V {
+        T::on_drop_with_reason(reason)
+    }
+
+    fn deliver(self, result: V) {
+        self.complete(result)
+    }
+
+    fn try_deliver(self, result: V) -> Result<(), V> {
+        self.try_complete(result)
+    }
+}
+
+/// A Promise is a type that is guaranteed to complete its underlying FutureType,
+/// even if it is dropped.
+#[derive(Debug)]
+pub struct Promise<T, V> {
     inner: Option<T>,
-    _v: std::marker::PhantomData<V>,
+    complete: fn(T, V) -> Result<(), V>,
+    abandon: fn(T, DropReason),
+    location: &'static std::panic::Location<'static>,
+    #[cfg(any(feature = "registry", feature = "tracing"))]
+    settled: fn(&T) -> bool,
+    #[cfg(feature = "registry")]
+    tracker: registry::Tracker,
+    #[cfg(feature = "tracing")]
+    trace: trace::Trace,
 }
 
 impl<T, V> Promise<T, V>
 where
-    T: FutureType<V>,
+    T: StatefulFutureType<V>,
 {
     /// Construct a promise from a FutureType
+    #[track_caller]
     fn new(inner: T) -> Self {
+        Self::from_parts(inner, T::try_deliver, |cb, reason| {
+            let value = cb.drop_value(reason);
+            cb.deliver(value);
+        })
+    }
+}
+
+impl<T, V> Promise<T, V> {
+    /// Construct a promise from an inner value and the functions used to complete it
+    #[track_caller]
+    pub(crate) fn from_parts(
+        inner: T,
+        complete: fn(T, V) -> Result<(), V>,
+        abandon: fn(T, DropReason),
+    ) -> Self {
+        Self::from_parts_at(inner, complete, abandon, std::panic::Location::caller())
+    }
+
+    /// Like [Promise::from_parts], but with an explicit creation site
+    ///
+    /// Used where `#[track_caller]` cannot reach the construction, e.g. inside a closure.
+    pub(crate) fn from_parts_at(
+        inner: T,
+        complete: fn(T, V) -> Result<(), V>,
+        abandon: fn(T, DropReason),
+        location: &'static std::panic::Location<'static>,
+    ) -> Self {
         Self {
             inner: Some(inner),
-            _v: Default::default(),
+            complete,
+            abandon,
+            location,
+            #[cfg(any(feature = "registry", feature = "tracing"))]
+            settled: |_| false,
+            #[cfg(feature = "registry")]
+            tracker: registry::Tracker::new::<T>(location),
+            #[cfg(feature = "tracing")]
+            trace: trace::Trace::new::<T>(location),
+        }
+    }
+
+    /// Use `settled` to tell whether the inner value was already completed by another path, e.g.
+    /// a cancellation token, so that dropping the promise is not reported as a leak
+    #[cfg_attr(
+        not(any(feature = "registry", feature = "tracing")),
+        allow(unused_mut, unused_variables)
+    )]
+    pub(crate) fn settled_when(mut self, settled: fn(&T) -> bool) -> Self {
+        #[cfg(any(feature = "registry", feature = "tracing"))]
+        {
+            self.settled = settled;
         }
+        self
+    }
+
+    /// Record a completion with the drop value, or a completion if the inner value was settled
+    #[cfg(any(feature = "registry", feature = "tracing"))]
+    #[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+    fn record_drop(&self, inner: &T, reason: DropReason, dropped: bool) {
+        if (self.settled)(inner) {
+            #[cfg(feature = "registry")]
+            self.tracker.completed();
+            #[cfg(feature = "tracing")]
+            self.trace.settled(reason);
+            return;
+        }
+        #[cfg(feature = "registry")]
+        self.tracker.dropped();
+        #[cfg(feature = "tracing")]
+        if dropped {
+            self.trace.dropped(reason);
+        } else {
+            self.trace.abandoned(reason);
+        }
+    }
+
+    /// Mutable access to the inner value while the promise is not yet completed
+    pub(crate) fn inner_mut(&mut self) -> Option<&mut T> {
+        self.inner.as_mut()
     }
 
     /// Complete the promise, consuming it
-    pub fn complete(mut self, result: V) {
+    pub fn complete(self, result: V) {
+        let _ = self.try_complete(result);
+    }
+
+    /// Complete the promise, consuming it and returning the value if it could not be delivered
+    ///
+    /// If the underlying callback panics and a panic hook is installed, the value was handed to
+    /// the callback and `Ok(())` is returned.
+    pub fn try_complete(mut self, result: V) -> Result<(), V> {
+        match self.inner.take() {
+            Some(x) => {
+                #[cfg(feature = "registry")]
+                self.tracker.completed();
+                #[cfg(feature = "tracing")]
+                self.trace.completed();
+                let complete = self.complete;
+                let _completing = reentrancy::Completing::enter(self.location);
+                panic::complete(|| complete(x, result)).unwrap_or(Ok(()))
+            }
+            None => Err(result),
+        }
+    }
+
+    /// Complete the promise with the drop value for the specified reason, consuming it
+    pub fn abandon(mut self, reason: DropReason) {
         if let Some(x) = self.inner.take() {
-            x.complete(result);
+            #[cfg(any(feature = "registry", feature = "tracing"))]
+            self.record_drop(&x, reason, false);
+            let abandon = self.abandon;
+            let _completing = reentrancy::Completing::enter(self.location);
+            panic::complete(|| abandon(x, reason));
         }
     }
 }
 
-/// Wrap a type that implements FutureType into a drop-safe promise
+/// Wrap a type that implements FutureType (or StatefulFutureType) into a drop-safe promise
+#[track_caller]
 pub fn wrap<T, V>(callback: T) -> Promise<T, V>
 where
-    T: FutureType<V>,
+    T: StatefulFutureType<V>,
 {
     Promise::new(callback)
 }
 
-impl<T, V> Drop for Promise<T, V>
-where
-    T: FutureType<V>,
-{
+impl<T, V> Drop for Promise<T, V> {
     fn drop(&mut self) {
-        if let Some(cb) = self.inner.take() {
-            cb.complete(T::on_drop());
+        let reason = if std::thread::panicking() {
+            DropReason::Panicking
+        } else {
+            DropReason::Abandoned
+        };
+        if let Some(x) = self.inner.take() {
+            #[cfg(any(feature = "registry", feature = "tracing"))]
+            self.record_drop(&x, reason, true);
+            let abandon = self.abandon;
+            let _completing = reentrancy::Completing::enter(self.location);
+            panic::complete_detached(|| abandon(x, reason));
         }
     }
 }
@@ -121,6 +353,21 @@ mod tests {
         }
     }
 
+    struct WithId<'a> {
+        id: u32,
+        vec: &'a mut Vec<Result<u32, String>>,
+    }
+
+    impl<'a> StatefulFutureType<Result<u32, String>> for WithId<'a> {
+        fn drop_value(&self, reason: DropReason) -> Result<u32, String> {
+            Err(format!("request {} {:?}", self.id, reason))
+        }
+
+        fn deliver(self, result: Result<u32, String>) {
+            self.vec.push(result);
+        }
+    }
+
     #[test]
     fn completes_on_drop() {
         let mut output = Vec::new();
@@ -143,4 +390,100 @@ mod tests {
         promise.complete(Err("fail"));
         assert_eq!(output.as_slice(), [Err("fail")]);
     }
+
+    #[test]
+    fn stateful_drop_value_carries_context() {
+        let mut output = Vec::new();
+        let _ = wrap(WithId {
+            id: 7,
+            vec: &mut output,
+        });
+        assert_eq!(output.as_slice(), [Err("request 7 Abandoned".to_string())]);
+    }
+
+    #[test]
+    fn stateful_completes_once_on_success() {
+        let mut output = Vec::new();
+        let promise = wrap(WithId {
+            id: 7,
+            vec: &mut output,
+        });
+        promise.complete(Ok(42));
+        assert_eq!(output.as_slice(), [Ok(42)]);
+    }
+
+    struct WithReason<'a> {
+        vec: &'a mut Vec<Result<u32, DropReason>>,
+    }
+
+    impl<'a> FutureType<Result<u32, DropReason>> for WithReason<'a> {
+        fn on_drop() -> Result<u32, DropReason> {
+            Err(DropReason::Abandoned)
+        }
+
+        fn on_drop_with_reason(reason: DropReason) -> Result<u32, DropReason> {
+            Err(reason)
+        }
+
+        fn complete(self, result: Result<u32, DropReason>) {
+            self.vec.push(result);
+        }
+    }
+
+    #[test]
+    fn abandon_passes_reason_to_drop_hook() {
+        let mut output = Vec::new();
+        wrap(WithReason { vec: &mut output }).abandon(DropReason::Shutdown);
+        assert_eq!(output.as_slice(), [Err(DropReason::Shutdown)]);
+    }
+
+    #[test]
+    fn static_drop_hook_defaults_to_on_drop() {
+        let mut output = Vec::new();
+        wrap(Borrowed { vec: &mut output }).abandon(DropReason::Cancelled);
+        assert_eq!(output.as_slice(), [Err("dropped")]);
+    }
+
+    #[test]
+    fn drop_while_panicking_reports_panicking() {
+        let output = std::sync::Mutex::new(Vec::new());
+        let _ = std::panic::catch_unwind(|| {
+            let mut guard = output.lock().unwrap();
+            let _promise = wrap(WithReason { vec: &mut guard });
+            panic!("boom");
+        });
+        let output = output.into_inner().unwrap_or_else(|e| e.into_inner());
+        assert_eq!(output.as_slice(), [Err(DropReason::Panicking)]);
+    }
+
+    struct Closed;
+
+    impl FutureType<u32> for Closed {
+        fn on_drop() -> u32 {
+            0
+        }
+
+        fn complete(self, result: u32) {
+            let _ = self.try_complete(result);
+        }
+
+        fn try_complete(self, result: u32) -> Result<(), u32> {
+            Err(result)
+        }
+    }
+
+    #[test]
+    fn try_complete_reports_delivery() {
+        let mut output = Vec::new();
+        assert_eq!(
+            wrap(Borrowed { vec: &mut output }).try_complete(Ok(1)),
+            Ok(())
+        );
+        assert_eq!(output.as_slice(), [Ok(1)]);
+    }
+
+    #[test]
+    fn try_complete_returns_undelivered_value() {
+        assert_eq!(wrap(Closed).try_complete(42), Err(42));
+    }
 }
@@ -0,0 +1,130 @@
+use crate::Promise;
+
+/// The inner value of a promise produced by [Promise::contramap], [Promise::map_input]
+/// or [Promise::and_then]
+///
+/// It holds the original promise, so dropping the new promise completes the original one
+/// with its own drop value. Since the input has already been converted, a value that the
+/// original promise fails to deliver cannot be returned from [Promise::try_complete].
+#[derive(Debug)]
+pub struct Contramap<T, V, F> {
+    promise: Promise<T, V>,
+    f: F,
+}
+
+type Mapped<T, V, F, U> = Promise<Contramap<T, V, F>, U>;
+
+impl<T, V> Promise<T, V> {
+    /// Create a promise accepting a different input type, which is converted with `f`
+    /// before completing this promise
+    #[track_caller]
+    pub fn contramap<U, F>(self, f: F) -> Mapped<T, V, F, U>
+    where
+        F: FnOnce(U) -> V,
+    {
+        Promise::from_parts(
+            Contramap { promise: self, f },
+            |inner: Contramap<T, V, F>, value: U| {
+                inner.promise.complete((inner.f)(value));
+                Ok(())
+            },
+            |inner, reason| inner.promise.abandon(reason),
+        )
+    }
+}
+
+impl<T, B, E> Promise<T, Result<B, E>> {
+    /// Create a promise whose success value is converted with `f` before completing this
+    /// promise. Errors are passed through unchanged.
+    #[track_caller]
+    pub fn map_input<A, F>(self, f: F) -> Mapped<T, Result<B, E>, F, Result<A, E>>
+    where
+        F: FnOnce(A) -> B,
+    {
+        Promise::from_parts(
+            Contramap { promise: self, f },
+            |inner: Contramap<T, Result<B, E>, F>, value: Result<A, E>| {
+                inner.promise.complete(value.map(inner.f));
+                Ok(())
+            },
+            |inner, reason| inner.promise.abandon(reason),
+        )
+    }
+
+    /// Create a promise whose success value is converted with the fallible `f` before
+    /// completing this promise. Errors are passed through unchanged.
+    #[track_caller]
+    pub fn and_then<A, F>(self, f: F) -> Mapped<T, Result<B, E>, F, Result<A, E>>
+    where
+        F: FnOnce(A) -> Result<B, E>,
+    {
+        Promise::from_parts(
+            Contramap { promise: self, f },
+            |inner: Contramap<T, Result<B, E>, F>, value: Result<A, E>| {
+                inner.promise.complete(value.and_then(inner.f));
+                Ok(())
+            },
+            |inner, reason| inner.promise.abandon(reason),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::*;
+    use crate::DropReason;
+
+    fn on_drop(reason: DropReason) -> Result<u32, String> {
+        Err(format!("{reason:?}"))
+    }
+
+    #[test]
+    fn contramap_converts_input() {
+        let (promise, recorder) = mock(on_drop);
+        let promise = promise.contramap(|x: &str| x.parse::<u32>().map_err(|e| e.to_string()));
+        promise.complete("42");
+        assert_completed_with(&recorder, Ok(42));
+    }
+
+    #[test]
+    fn map_input_converts_success_and_passes_errors() {
+        let (promise, recorder) = mock(on_drop);
+        promise.map_input(|x: u8| x as u32 * 2).complete(Ok(21));
+        assert_completed_with(&recorder, Ok(42));
+
+        let (promise, recorder) = mock(on_drop);
+        promise
+            .map_input(|x: u8| x as u32)
+            .complete(Err("fail".to_string()));
+        assert_completed_with(&recorder, Err("fail".to_string()));
+    }
+
+    #[test]
+    fn and_then_can_fail_conversion() {
+        let (promise, recorder) = mock(on_drop);
+        promise
+            .and_then(|x: i32| u32::try_from(x).map_err(|_| "negative".to_string()))
+            .complete(Ok(-1));
+        assert_completed_with(&recorder, Err("negative".to_string()));
+    }
+
+    #[test]
+    fn dropping_chain_completes_original_with_its_drop_value() {
+        let (promise, recorder) = mock(on_drop);
+        let promise = promise
+            .map_input(|x: u16| x as u32)
+            .contramap(|x: u8| Ok(x as u16));
+        drop(promise);
+        assert_eq!(assert_dropped(&recorder), DropReason::Abandoned);
+        assert_eq!(expect_exactly_once(&recorder), Err("Abandoned".to_string()));
+    }
+
+    #[test]
+    fn abandoning_chain_forwards_reason() {
+        let (promise, recorder) = mock(on_drop);
+        promise
+            .contramap(|x: u32| Ok(x))
+            .abandon(DropReason::Shutdown);
+        assert_eq!(assert_dropped(&recorder), DropReason::Shutdown);
+    }
+}
@@ -0,0 +1,111 @@
+use std::any::Any;
+use std::panic::AssertUnwindSafe;
+use std::sync::{Arc, RwLock};
+
+type Hook = Arc<dyn Fn(Box<dyn Any + Send>) + Send + Sync>;
+
+static HOOK: RwLock<Option<Hook>> = RwLock::new(None);
+
+/// Catch panics raised while completing promises and pass their payload to `hook`
+///
+/// While a hook is installed, [Promise::complete](crate::Promise::complete) and
+/// [Promise::abandon](crate::Promise::abandon) never unwind into the caller.
+/// Completions performed when a promise is dropped always catch panics, whether or not a
+/// hook is installed, so dropping a promise never panics.
+pub fn set_panic_hook<F>(hook: F)
+where
+    F: Fn(Box<dyn Any + Send>) + Send + Sync + 'static,
+{
+    *HOOK.write().unwrap_or_else(|e| e.into_inner()) = Some(Arc::new(hook));
+}
+
+/// Remove the hook installed by [set_panic_hook]
+///
+/// Panics raised by explicit completions propagate to the caller again.
+pub fn clear_panic_hook() {
+    *HOOK.write().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
+fn hook() -> Option<Hook> {
+    HOOK.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Run an explicit completion, catching any panic if a hook is installed
+///
+/// Returns `None` if a panic was caught.
+pub(crate) fn complete<R>(f: impl FnOnce() -> R) -> Option<R> {
+    match hook() {
+        Some(hook) => match std::panic::catch_unwind(AssertUnwindSafe(f)) {
+            Ok(x) => Some(x),
+            Err(payload) => {
+                hook(payload);
+                None
+            }
+        },
+        None => Some(f()),
+    }
+}
+
+/// Run a completion that has no caller to unwind into, such as from `Drop` or a dispatcher
+/// thread, always catching any panic
+///
+/// Without a hook, the panic has already been reported by the standard panic hook.
+pub(crate) fn complete_detached(f: impl FnOnce()) {
+    if let Err(payload) = std::panic::catch_unwind(AssertUnwindSafe(f)) {
+        if let Some(hook) = hook() {
+            hook(payload);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{wrap, FutureType};
+    use std::sync::Mutex;
+
+    struct Panics;
+
+    impl FutureType<u32> for Panics {
+        fn on_drop() -> u32 {
+            0
+        }
+
+        fn complete(self, result: u32) {
+            panic!("complete panicked: {result}");
+        }
+    }
+
+    fn message(payload: &(dyn Any + Send)) -> String {
+        payload
+            .downcast_ref::<String>()
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn drop_never_panics_without_hook() {
+        drop(wrap(Panics));
+    }
+
+    #[test]
+    fn hook_receives_completion_panics() {
+        static CAUGHT: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+        set_panic_hook(|payload| CAUGHT.lock().unwrap().push(message(payload.as_ref())));
+        wrap(Panics).complete(1);
+        drop(wrap(Panics));
+        clear_panic_hook();
+
+        // other tests may drop panicking promises concurrently, so only check for ours
+        let caught = CAUGHT.lock().unwrap();
+        assert!(caught.iter().any(|x| x == "complete panicked: 1"));
+        assert!(caught.iter().any(|x| x == "complete panicked: 0"));
+
+        let result = std::panic::catch_unwind(|| wrap(Panics).complete(2));
+        assert_eq!(
+            message(result.unwrap_err().as_ref()),
+            "complete panicked: 2"
+        );
+    }
+}
@@ -0,0 +1,289 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::time::Instant;
+
+use crate::Promise;
+
+/// Identifiers that a [PendingMap] can allocate by incrementing, wrapping on overflow
+pub trait PendingId: Copy + Eq + Hash {
+    /// The identifier following this one
+    fn next(self) -> Self;
+}
+
+macro_rules! impl_pending_id {
+    ($($t:ty),*) => {
+        $(
+            impl PendingId for $t {
+                fn next(self) -> Self {
+                    self.wrapping_add(1)
+                }
+            }
+        )*
+    };
+}
+
+impl_pending_id!(u8, u16, u32, u64, usize);
+
+/// Why [PendingMap::complete] failed, carrying the value that was not delivered
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CompleteError<V> {
+    /// No entry with the id is pending, e.g. the response is late or unsolicited
+    UnknownKey(V),
+    /// The entry was removed, but its callback could not accept the value
+    NotDelivered(V),
+}
+
+impl<V> CompleteError<V> {
+    /// The value that was not delivered
+    pub fn into_value(self) -> V {
+        match self {
+            CompleteError::UnknownKey(x) => x,
+            CompleteError::NotDelivered(x) => x,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Entry<T, V> {
+    promise: Promise<T, V>,
+    deadline: Option<Instant>,
+}
+
+/// A table of pending requests keyed by transaction id
+///
+/// Responses complete entries by id, [PendingMap::expire] completes entries whose deadline
+/// has passed, and [PendingMap::fail_all] completes every entry when the link is lost.
+/// Entries remaining when the map is dropped are completed with their drop value.
+#[derive(Debug)]
+pub struct PendingMap<K, T, V> {
+    entries: HashMap<K, Entry<T, V>>,
+    next_id: K,
+}
+
+impl<K, T, V> Default for PendingMap<K, T, V>
+where
+    K: PendingId + Default,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, T, V> PendingMap<K, T, V>
+where
+    K: PendingId,
+{
+    /// Create an empty map that allocates ids starting from the default value, usually zero
+    pub fn new() -> Self
+    where
+        K: Default,
+    {
+        Self::starting_at(K::default())
+    }
+
+    /// Create an empty map that allocates ids starting from `first`
+    pub fn starting_at(first: K) -> Self {
+        Self {
+            entries: HashMap::new(),
+            next_id: first,
+        }
+    }
+
+    /// Number of pending entries
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if no entry is pending
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns true if an entry with the specified id is pending
+    pub fn contains(&self, key: K) -> bool {
+        self.entries.contains_key(&key)
+    }
+
+    /// Insert a promise under a newly allocated id, skipping ids that are still pending
+    ///
+    /// If every id is in use, the promise is returned.
+    pub fn insert(&mut self, promise: Promise<T, V>) -> Result<K, Promise<T, V>> {
+        self.insert_entry(promise, None)
+    }
+
+    /// Like [PendingMap::insert], but the entry is expired by [PendingMap::expire] once
+    /// `deadline` has passed
+    pub fn insert_with_deadline(
+        &mut self,
+        promise: Promise<T, V>,
+        deadline: Instant,
+    ) -> Result<K, Promise<T, V>> {
+        self.insert_entry(promise, Some(deadline))
+    }
+
+    fn insert_entry(
+        &mut self,
+        promise: Promise<T, V>,
+        deadline: Option<Instant>,
+    ) -> Result<K, Promise<T, V>> {
+        // among len + 1 consecutive ids at least one is free, unless the id space is exhausted
+        let mut id = self.next_id;
+        for _ in 0..=self.entries.len() {
+            if !self.entries.contains_key(&id) {
+                self.next_id = id.next();
+                self.entries.insert(id, Entry { promise, deadline });
+                return Ok(id);
+            }
+            id = id.next();
+        }
+        Err(promise)
+    }
+
+    /// Remove the entry with the specified id without completing it
+    pub fn remove(&mut self, key: K) -> Option<Promise<T, V>> {
+        self.entries.remove(&key).map(|x| x.promise)
+    }
+
+    /// Complete the entry with the specified id
+    ///
+    /// The entry is removed even if its callback could not accept the value.
+    pub fn complete(&mut self, key: K, value: V) -> Result<(), CompleteError<V>> {
+        match self.entries.remove(&key) {
+            Some(entry) => entry
+                .promise
+                .try_complete(value)
+                .map_err(CompleteError::NotDelivered),
+            None => Err(CompleteError::UnknownKey(value)),
+        }
+    }
+
+    /// The earliest deadline among the pending entries
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.entries.values().filter_map(|x| x.deadline).min()
+    }
+
+    /// Complete every entry whose deadline is at or before `now` with `timeout_value`,
+    /// returning the number of entries completed
+    pub fn expire(&mut self, now: Instant, timeout_value: V) -> usize
+    where
+        V: Clone,
+    {
+        let expired: Vec<K> = self
+            .entries
+            .iter()
+            .filter(|(_, entry)| entry.deadline.is_some_and(|x| x <= now))
+            .map(|(key, _)| *key)
+            .collect();
+        for key in expired.iter() {
+            if let Some(entry) = self.entries.remove(key) {
+                entry.promise.complete(timeout_value.clone());
+            }
+        }
+        expired.len()
+    }
+
+    /// Complete every entry with `value`, returning the number of entries completed
+    pub fn fail_all(&mut self, value: V) -> usize
+    where
+        V: Clone,
+    {
+        let count = self.entries.len();
+        for (_, entry) in self.entries.drain() {
+            entry.promise.complete(value.clone());
+        }
+        count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+    use crate::{blocking_channel, drop_error, PromiseError};
+    use std::time::Duration;
+
+    type Value = Result<u32, PromiseError>;
+
+    fn pending() -> (Promise<MockCallback<Value>, Value>, Recorder<Value>) {
+        mock(drop_error)
+    }
+
+    #[test]
+    fn completes_entries_by_id() {
+        let mut map: PendingMap<u8, _, _> = PendingMap::new();
+        let (first, first_recorder) = pending();
+        let (second, second_recorder) = pending();
+        assert_eq!(map.insert(first).unwrap(), 0);
+        assert_eq!(map.insert(second).unwrap(), 1);
+
+        assert_eq!(map.complete(1, Ok(2)), Ok(()));
+        assert_eq!(
+            map.complete(1, Ok(3)),
+            Err(CompleteError::UnknownKey(Ok(3)))
+        );
+        assert_completed_with(&second_recorder, Ok(2));
+        assert!(map.contains(0));
+
+        drop(map);
+        assert_eq!(
+            expect_exactly_once(&first_recorder),
+            Err(PromiseError::Dropped)
+        );
+    }
+
+    #[test]
+    fn reports_values_that_could_not_be_delivered() {
+        let mut map: PendingMap<u8, _, _> = PendingMap::new();
+        let (promise, handle) = blocking_channel(drop_error::<u32, PromiseError>);
+        let id = map.insert(promise).unwrap();
+        drop(handle);
+        assert_eq!(
+            map.complete(id, Ok(1)),
+            Err(CompleteError::NotDelivered(Ok(1)))
+        );
+        assert!(map.is_empty());
+    }
+
+    #[test]
+    fn allocation_wraps_and_skips_pending_ids() {
+        let mut map: PendingMap<u8, _, _> = PendingMap::starting_at(255);
+        assert_eq!(map.insert(pending().0).unwrap(), 255);
+        assert_eq!(map.insert(pending().0).unwrap(), 0);
+
+        let mut map: PendingMap<u8, _, _> = PendingMap::starting_at(0);
+        for _ in 0..256 {
+            map.insert(pending().0).unwrap();
+        }
+        let (promise, recorder) = pending();
+        assert!(map.insert(promise).is_err());
+        assert_eq!(expect_exactly_once(&recorder), Err(PromiseError::Dropped));
+
+        map.remove(7).unwrap().complete(Ok(7));
+        assert_eq!(map.insert(pending().0).unwrap(), 7);
+    }
+
+    #[test]
+    fn expires_entries_past_their_deadline() {
+        let now = Instant::now();
+        let mut map: PendingMap<u16, _, _> = PendingMap::new();
+        let (early, early_recorder) = pending();
+        let (late, late_recorder) = pending();
+        let (none, none_recorder) = pending();
+        map.insert_with_deadline(early, now).unwrap();
+        let late = map
+            .insert_with_deadline(late, now + Duration::from_secs(10))
+            .unwrap();
+        map.insert(none).unwrap();
+        assert_eq!(map.next_deadline(), Some(now));
+
+        assert_eq!(map.expire(now, Err(PromiseError::TimedOut)), 1);
+        assert_completed_with(&early_recorder, Err(PromiseError::TimedOut));
+        assert_eq!(map.next_deadline(), Some(now + Duration::from_secs(10)));
+        map.complete(late, Ok(1)).unwrap();
+        assert_completed_with(&late_recorder, Ok(1));
+
+        assert_eq!(map.fail_all(Err(PromiseError::Shutdown)), 1);
+        assert!(map.is_empty());
+        assert_completed_with(&none_recorder, Err(PromiseError::Shutdown));
+    }
+}
@@ -0,0 +1,260 @@
+use std::panic::Location;
+use std::sync::{Arc, Mutex};
+
+use crate::{DropReason, Promise};
+
+#[derive(Debug)]
+struct RaceState<T, V> {
+    parent: Option<Promise<T, (usize, V)>>,
+    remaining: usize,
+}
+
+/// The inner value of a child promise produced by [race]
+#[derive(Debug)]
+pub struct RaceChild<T, V> {
+    state: Arc<Mutex<RaceState<T, V>>>,
+    index: usize,
+}
+
+/// Split a promise into `count` child promises racing to complete it
+///
+/// The parent is completed with the index and value of the first child to complete. Later
+/// completions are discarded and [Promise::try_complete] returns their value. If every child
+/// is dropped without being completed, the parent is completed with its drop value.
+#[track_caller]
+pub fn race<T, V>(
+    parent: Promise<T, (usize, V)>,
+    count: usize,
+) -> Vec<Promise<RaceChild<T, V>, V>> {
+    if count == 0 {
+        parent.abandon(DropReason::Abandoned);
+        return Vec::new();
+    }
+    let location = Location::caller();
+    let state = Arc::new(Mutex::new(RaceState {
+        parent: Some(parent),
+        remaining: count,
+    }));
+    (0..count)
+        .map(|index| {
+            Promise::from_parts_at(
+                RaceChild {
+                    state: state.clone(),
+                    index,
+                },
+                RaceChild::complete,
+                RaceChild::abandon,
+                location,
+            )
+        })
+        .collect()
+}
+
+impl<T, V> RaceChild<T, V> {
+    fn complete(self, value: V) -> Result<(), V> {
+        let parent = self.state.lock().unwrap().parent.take();
+        match parent {
+            Some(parent) => {
+                parent.complete((self.index, value));
+                Ok(())
+            }
+            None => Err(value),
+        }
+    }
+
+    fn abandon(self, reason: DropReason) {
+        let parent = {
+            let mut state = self.state.lock().unwrap();
+            state.remaining -= 1;
+            if state.remaining > 0 {
+                return;
+            }
+            state.parent.take()
+        };
+        if let Some(parent) = parent {
+            parent.abandon(reason);
+        }
+    }
+}
+
+/// The value of a promise completed by [any]: the index and value of the first child to
+/// succeed, or the errors of every child
+pub type AnyResult<X, E> = Result<(usize, X), Vec<E>>;
+
+type AnyChildren<T, X, E> = Vec<Promise<AnyChild<T, X, E>, Result<X, E>>>;
+
+#[derive(Debug)]
+struct AnyState<T, X, E> {
+    parent: Option<Promise<T, AnyResult<X, E>>>,
+    errors: Vec<Option<E>>,
+    remaining: usize,
+}
+
+/// The inner value of a child promise produced by [any]
+#[derive(Debug)]
+pub struct AnyChild<T, X, E> {
+    state: Arc<Mutex<AnyState<T, X, E>>>,
+    index: usize,
+    on_drop: fn(DropReason) -> E,
+}
+
+/// Split a promise into `count` child promises, the first successful one completing it
+///
+/// The parent is completed with the index and value of the first child to succeed. If every
+/// child fails, the parent is completed with the errors of the children, in order. A child
+/// that is dropped fails with the error produced by `on_drop`.
+#[track_caller]
+pub fn any<T, X, E>(
+    parent: Promise<T, AnyResult<X, E>>,
+    count: usize,
+    on_drop: fn(DropReason) -> E,
+) -> AnyChildren<T, X, E> {
+    if count == 0 {
+        parent.complete(Err(Vec::new()));
+        return Vec::new();
+    }
+    let location = Location::caller();
+    let state = Arc::new(Mutex::new(AnyState {
+        parent: Some(parent),
+        errors: (0..count).map(|_| None).collect(),
+        remaining: count,
+    }));
+    (0..count)
+        .map(|index| {
+            Promise::from_parts_at(
+                AnyChild {
+                    state: state.clone(),
+                    index,
+                    on_drop,
+                },
+                AnyChild::complete,
+                |child, reason| {
+                    let error = (child.on_drop)(reason);
+                    let _ = child.complete(Err(error));
+                },
+                location,
+            )
+        })
+        .collect()
+}
+
+impl<T, X, E> AnyChild<T, X, E> {
+    fn complete(self, value: Result<X, E>) -> Result<(), Result<X, E>> {
+        let (parent, result) = {
+            let mut state = self.state.lock().unwrap();
+            if state.parent.is_none() {
+                // another child already won, or every child failed
+                return Err(value);
+            }
+            match value {
+                Ok(x) => (state.parent.take(), Ok((self.index, x))),
+                Err(e) => {
+                    state.errors[self.index] = Some(e);
+                    state.remaining -= 1;
+                    if state.remaining > 0 {
+                        return Ok(());
+                    }
+                    let errors = state.errors.drain(..).flatten().collect();
+                    (state.parent.take(), Err(errors))
+                }
+            }
+        };
+        if let Some(parent) = parent {
+            parent.complete(result);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+
+    fn on_drop(reason: DropReason) -> String {
+        format!("{reason:?}")
+    }
+
+    #[test]
+    fn race_completes_parent_with_first_child() {
+        let (promise, recorder) = mock(Err);
+        let mut children = race(promise.contramap(Ok), 2);
+        let backup = children.pop().unwrap();
+        let primary = children.pop().unwrap();
+        backup.complete("backup");
+        assert_eq!(primary.try_complete("primary"), Err("primary"));
+        assert_completed_with(&recorder, Ok((1, "backup")));
+    }
+
+    #[test]
+    fn race_drops_parent_when_every_child_dropped() {
+        let (promise, recorder) = mock(Err::<(usize, u32), _>);
+        let children = race(promise.contramap(Ok), 2);
+        drop(children);
+        assert_eq!(assert_dropped(&recorder), DropReason::Abandoned);
+    }
+
+    #[test]
+    fn race_ignores_dropped_children_while_one_remains() {
+        let (promise, recorder) = mock(Err);
+        let mut children = race(promise.contramap(Ok), 2);
+        drop(children.pop());
+        children.pop().unwrap().complete(7u32);
+        assert_completed_with(&recorder, Ok((0, 7)));
+    }
+
+    #[cfg(feature = "registry")]
+    #[test]
+    fn children_record_caller_as_creation_site() {
+        let (race_promise, _race_recorder) = mock(Err::<(usize, u32), _>);
+        let (any_promise, _any_recorder) = mock(Err::<AnyResult<u32, String>, _>);
+        let _race = race(race_promise.contramap(Ok), 2);
+        let race_line = line!() - 1;
+        let _any = any(any_promise.contramap(Ok), 2, on_drop);
+        let any_line = line!() - 1;
+
+        let outstanding = crate::registry::outstanding();
+        let created_at = |name: &str, line: u32| {
+            outstanding
+                .iter()
+                .filter(|x| x.type_name.contains(name))
+                .filter(|x| x.location.file() == file!() && x.location.line() == line)
+                .count()
+        };
+        assert_eq!(created_at("RaceChild", race_line), 2);
+        assert_eq!(created_at("AnyChild", any_line), 2);
+    }
+
+    #[test]
+    fn any_completes_parent_with_first_success() {
+        let (promise, recorder) = mock(Err);
+        let mut children = any(promise.contramap(Ok), 3, on_drop);
+        let third = children.pop().unwrap();
+        let second = children.pop().unwrap();
+        children
+            .pop()
+            .unwrap()
+            .complete(Err("primary failed".to_string()));
+        second.complete(Ok(2u32));
+        assert_eq!(third.try_complete(Ok(3)), Err(Ok(3)));
+        assert_completed_with(&recorder, Ok(Ok((1, 2))));
+    }
+
+    #[test]
+    fn any_merges_errors_when_every_child_fails() {
+        let (promise, recorder) = mock(Err::<AnyResult<u32, String>, _>);
+        let mut children = any(promise.contramap(Ok), 2, on_drop);
+        children
+            .pop()
+            .unwrap()
+            .complete(Err("backup failed".to_string()));
+        drop(children);
+        assert_completed_with(
+            &recorder,
+            Ok(Err(vec![
+                "Abandoned".to_string(),
+                "backup failed".to_string(),
+            ])),
+        );
+    }
+}
@@ -0,0 +1,134 @@
+use std::panic::Location;
+
+#[cfg(debug_assertions)]
+thread_local! {
+    static COMPLETING: std::cell::RefCell<Vec<&'static Location<'static>>> =
+        const { std::cell::RefCell::new(Vec::new()) };
+}
+
+/// Returns the creation site of the promise currently being completed on this thread, if any
+///
+/// Completions are only tracked in debug builds; release builds always return `None`.
+pub fn in_callback() -> Option<&'static Location<'static>> {
+    #[cfg(debug_assertions)]
+    {
+        COMPLETING
+            .try_with(|x| x.borrow().last().copied())
+            .ok()
+            .flatten()
+    }
+    #[cfg(not(debug_assertions))]
+    {
+        None
+    }
+}
+
+/// Assert that the current thread is not completing a promise
+///
+/// Library code should call this before taking a lock that a callback re-entering the
+/// library could also take. In debug builds, a violation panics with the creation site of the
+/// promise being completed. In release builds this does nothing.
+#[track_caller]
+pub fn not_in_callback() {
+    if let Some(location) = in_callback() {
+        panic!("called from the completion callback of the promise created at {location}");
+    }
+}
+
+/// Marks the current thread as completing a promise until dropped
+///
+/// Promises may be dropped while thread locals are being destroyed, after which completions
+/// are no longer tracked on that thread.
+#[derive(Debug)]
+pub(crate) struct Completing {
+    _private: (),
+}
+
+impl Completing {
+    pub(crate) fn enter(location: &'static Location<'static>) -> Self {
+        #[cfg(debug_assertions)]
+        let _ = COMPLETING.try_with(|x| x.borrow_mut().push(location));
+        #[cfg(not(debug_assertions))]
+        let _ = location;
+        Self { _private: () }
+    }
+}
+
+impl Drop for Completing {
+    fn drop(&mut self) {
+        #[cfg(debug_assertions)]
+        let _ = COMPLETING.try_with(|x| x.borrow_mut().pop());
+    }
+}
+
+#[cfg(all(test, debug_assertions))]
+mod tests {
+    use super::*;
+    use crate::{wrap, FutureType};
+    use std::sync::mpsc::{channel, Sender};
+
+    struct Reentrant {
+        output: Sender<Result<(), String>>,
+    }
+
+    impl FutureType<()> for Reentrant {
+        fn on_drop() {}
+
+        fn complete(self, _result: ()) {
+            let result = std::panic::catch_unwind(not_in_callback)
+                .map_err(|err| err.downcast_ref::<String>().cloned().unwrap_or_default());
+            self.output.send(result).unwrap();
+        }
+    }
+
+    #[test]
+    fn not_in_callback_passes_outside_completion() {
+        not_in_callback();
+        assert!(in_callback().is_none());
+    }
+
+    #[test]
+    fn not_in_callback_reports_creation_site_of_completing_promise() {
+        let (tx, rx) = channel();
+        let promise = wrap(Reentrant { output: tx });
+        let line = line!() - 1;
+        promise.complete(());
+
+        let message = rx.recv().unwrap().unwrap_err();
+        assert!(
+            message.contains(&format!("{}:{line}", file!())),
+            "{message}"
+        );
+        assert!(in_callback().is_none());
+    }
+
+    #[test]
+    fn drop_during_thread_local_destruction_does_not_panic() {
+        thread_local! {
+            static HELD: std::cell::RefCell<Option<crate::Promise<Reentrant, ()>>> =
+                const { std::cell::RefCell::new(None) };
+        }
+
+        // destruction order follows registration order, so try both
+        for completing_first in [true, false] {
+            let (tx, rx) = channel();
+            std::thread::spawn(move || {
+                if completing_first {
+                    let _ = in_callback();
+                }
+                HELD.with(|x| *x.borrow_mut() = Some(wrap(Reentrant { output: tx })));
+                let _ = in_callback();
+            })
+            .join()
+            .unwrap();
+            assert!(rx.recv().is_ok());
+        }
+    }
+
+    #[test]
+    fn detects_reentrancy_from_drop_completion() {
+        let (tx, rx) = channel();
+        drop(wrap(Reentrant { output: tx }));
+        assert!(rx.recv().unwrap().is_err());
+    }
+}
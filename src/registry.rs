@@ -0,0 +1,154 @@
+//! Instrumentation of every live [Promise](crate::Promise)
+//!
+//! Each promise is recorded when it is created and removed when it is completed or dropped,
+//! which allows leaks to be detected in tests and reported by health endpoints.
+
+use std::collections::BTreeMap;
+use std::panic::Location;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+static LIVE: Mutex<BTreeMap<u64, PromiseInfo>> = Mutex::new(BTreeMap::new());
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+static CREATED: AtomicU64 = AtomicU64::new(0);
+static COMPLETED: AtomicU64 = AtomicU64::new(0);
+static DROPPED: AtomicU64 = AtomicU64::new(0);
+
+/// Information about a promise that has not yet been completed
+#[derive(Copy, Clone, Debug)]
+pub struct PromiseInfo {
+    /// Unique id assigned to the promise
+    pub id: u64,
+    /// Type name of the FutureType wrapped by the promise
+    pub type_name: &'static str,
+    /// Location in the source where the promise was created
+    pub location: &'static Location<'static>,
+    /// Time at which the promise was created
+    pub created: Instant,
+}
+
+impl PromiseInfo {
+    /// Time elapsed since the promise was created
+    pub fn age(&self) -> Duration {
+        self.created.elapsed()
+    }
+}
+
+/// Counts of promises since the start of the process
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Counters {
+    /// Number of promises created
+    pub created: u64,
+    /// Number of promises completed with a value
+    pub completed: u64,
+    /// Number of promises completed with a drop value, either by being dropped or abandoned
+    pub dropped: u64,
+}
+
+impl Counters {
+    /// Number of promises that have been created but not yet completed
+    pub fn outstanding(&self) -> u64 {
+        // the counters are read independently, so a snapshot may briefly be inconsistent
+        self.created
+            .saturating_sub(self.completed)
+            .saturating_sub(self.dropped)
+    }
+}
+
+/// Snapshot of every promise that has not yet been completed, oldest first
+pub fn outstanding() -> Vec<PromiseInfo> {
+    lock().values().copied().collect()
+}
+
+/// Current values of the promise counters
+pub fn counters() -> Counters {
+    Counters {
+        created: CREATED.load(Ordering::Relaxed),
+        completed: COMPLETED.load(Ordering::Relaxed),
+        dropped: DROPPED.load(Ordering::Relaxed),
+    }
+}
+
+fn lock() -> std::sync::MutexGuard<'static, BTreeMap<u64, PromiseInfo>> {
+    LIVE.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Registration of a single promise
+#[derive(Debug)]
+pub(crate) struct Tracker {
+    id: u64,
+}
+
+impl Tracker {
+    pub(crate) fn new<T>(location: &'static Location<'static>) -> Self {
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        let info = PromiseInfo {
+            id,
+            type_name: std::any::type_name::<T>(),
+            location,
+            created: Instant::now(),
+        };
+        lock().insert(id, info);
+        CREATED.fetch_add(1, Ordering::Relaxed);
+        Self { id }
+    }
+
+    pub(crate) fn completed(&self) {
+        lock().remove(&self.id);
+        COMPLETED.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn dropped(&self) {
+        lock().remove(&self.id);
+        DROPPED.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{wrap, FutureType};
+
+    struct Tracked;
+
+    impl FutureType<u32> for Tracked {
+        fn on_drop() -> u32 {
+            0
+        }
+
+        fn complete(self, _result: u32) {}
+    }
+
+    fn tracked() -> Vec<PromiseInfo> {
+        outstanding()
+            .into_iter()
+            .filter(|x| x.type_name.ends_with("Tracked"))
+            .collect()
+    }
+
+    #[test]
+    fn records_outstanding_promises_until_completed_or_dropped() {
+        let before = counters();
+        let first = wrap(Tracked);
+        let line = line!() - 1;
+        let second = wrap(Tracked);
+
+        let live = tracked();
+        assert_eq!(live.len(), 2);
+        assert_eq!(live[0].location.file(), file!());
+        assert_eq!(live[0].location.line(), line);
+        assert!(live[0].created <= live[1].created);
+
+        first.complete(1);
+        assert_eq!(tracked().len(), 1);
+        drop(second);
+        assert!(tracked().is_empty());
+
+        // other tests create promises concurrently, so only check that ours were counted
+        let after = counters();
+        assert!(after.created >= before.created + 2);
+        assert!(after.completed > before.completed);
+        assert!(after.dropped > before.dropped);
+    }
+}
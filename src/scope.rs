@@ -0,0 +1,229 @@
+use std::sync::{Arc, Mutex, Weak};
+
+use crate::actions::Actions;
+use crate::{DropReason, Promise, SharedPromise};
+
+#[derive(Debug)]
+struct State<V> {
+    actions: Actions<V>,
+    shut_down: bool,
+}
+
+/// A group of promises that can all be completed at once when shutting down
+///
+/// Promises registered in the scope are tracked until they are completed. [PromiseScope::shutdown]
+/// synchronously completes every pending promise and rejects further registrations, so that
+/// foreign callers are never left waiting. Clones of a scope share the same promises.
+#[derive(Debug)]
+pub struct PromiseScope<V> {
+    state: Arc<Mutex<State<V>>>,
+}
+
+impl<V> Clone for PromiseScope<V> {
+    fn clone(&self) -> Self {
+        Self {
+            state: self.state.clone(),
+        }
+    }
+}
+
+impl<V> Default for PromiseScope<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V> PromiseScope<V> {
+    /// Create an empty scope
+    pub fn new() -> Self {
+        Self {
+            state: Arc::new(Mutex::new(State {
+                actions: Actions::default(),
+                shut_down: false,
+            })),
+        }
+    }
+
+    /// Number of registered promises that have not yet been completed
+    pub fn len(&self) -> usize {
+        self.state.lock().unwrap().actions.len()
+    }
+
+    /// Returns true if no registered promise is pending
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns true if [PromiseScope::shutdown] has been called
+    pub fn is_shut_down(&self) -> bool {
+        self.state.lock().unwrap().shut_down
+    }
+
+    /// Complete every pending promise with `value` and reject further registrations,
+    /// returning the number of promises completed
+    ///
+    /// Promises completed concurrently by another path are not counted.
+    pub fn shutdown(&self, value: V) -> usize
+    where
+        V: Clone,
+    {
+        let actions = {
+            let mut state = self.state.lock().unwrap();
+            state.shut_down = true;
+            state.actions.take_all()
+        };
+        actions.run(value)
+    }
+}
+
+impl<V> PromiseScope<V>
+where
+    V: Send + 'static,
+{
+    /// Register a promise in the scope
+    ///
+    /// If the scope has already been shut down, the promise is returned as the error.
+    #[track_caller]
+    pub fn register<T>(
+        &self,
+        promise: Promise<T, V>,
+    ) -> Result<Promise<Scoped<T, V>, V>, Promise<T, V>>
+    where
+        T: Send + 'static,
+    {
+        let mut state = self.state.lock().unwrap();
+        if state.shut_down {
+            return Err(promise);
+        }
+        let shared = SharedPromise::new(promise);
+        let pending = shared.clone();
+        let id = state
+            .actions
+            .insert(Box::new(move |value| pending.try_complete(value)));
+        Ok(Promise::from_parts(
+            Scoped {
+                shared,
+                scope: Arc::downgrade(&self.state),
+                id,
+            },
+            |inner, value| {
+                inner.unregister();
+                inner.shared.try_deliver(value)
+            },
+            |inner, reason: DropReason| {
+                inner.unregister();
+                inner.shared.try_abandon(reason);
+            },
+        )
+        .settled_when(|inner| inner.shared.is_completed()))
+    }
+}
+
+/// The inner value of a promise produced by [PromiseScope::register]
+#[derive(Debug)]
+pub struct Scoped<T, V> {
+    shared: SharedPromise<T, V>,
+    scope: Weak<Mutex<State<V>>>,
+    id: u64,
+}
+
+impl<T, V> Scoped<T, V> {
+    fn unregister(&self) {
+        if let Some(state) = self.scope.upgrade() {
+            let action = state.lock().unwrap().actions.remove(self.id);
+            drop(action);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+
+    fn shutdown() -> Result<u32, DropReason> {
+        Err(DropReason::Shutdown)
+    }
+
+    #[test]
+    fn shutdown_completes_pending_promises() {
+        let scope = PromiseScope::new();
+        let (first, first_recorder) = mock(Err);
+        let (second, second_recorder) = mock(Err);
+        let _first = scope.register(first).unwrap();
+        let second = scope.register(second).unwrap();
+        assert_eq!(scope.len(), 2);
+
+        assert_eq!(scope.shutdown(shutdown()), 2);
+        assert!(scope.is_empty());
+        assert_completed_with(&first_recorder, shutdown());
+        assert_completed_with(&second_recorder, shutdown());
+        assert_eq!(second.try_complete(Ok(1)), Err(Ok(1)));
+    }
+
+    #[test]
+    fn completed_promises_leave_the_scope() {
+        let scope = PromiseScope::new();
+        let (promise, recorder) = mock(Err);
+        scope.register(promise).unwrap().complete(Ok(1));
+        assert!(scope.is_empty());
+        assert_eq!(scope.shutdown(shutdown()), 0);
+        assert_completed_with(&recorder, Ok(1));
+    }
+
+    #[test]
+    fn dropped_promises_leave_the_scope() {
+        let scope = PromiseScope::new();
+        let (promise, recorder) = mock(Err::<u32, _>);
+        drop(scope.register(promise).unwrap());
+        assert!(scope.is_empty());
+        assert_eq!(assert_dropped(&recorder), DropReason::Abandoned);
+    }
+
+    #[test]
+    fn panicking_callback_does_not_stop_shutdown() {
+        let scope = PromiseScope::new();
+        let _first = scope
+            .register(crate::from_fn(
+                |_: Result<u32, DropReason>| panic!("callback panicked"),
+                Err,
+            ))
+            .unwrap();
+        let (second, recorder) = mock(Err);
+        let _second = scope.register(second).unwrap();
+        assert_eq!(scope.shutdown(shutdown()), 2);
+        assert_completed_with(&recorder, shutdown());
+    }
+
+    #[test]
+    fn shutdown_counts_only_promises_it_completed() {
+        type Slot = Arc<Mutex<Option<crate::BoxPromise<Result<u32, DropReason>>>>>;
+        let scope = PromiseScope::new();
+        let slots: [Slot; 2] = Default::default();
+        for (i, slot) in slots.iter().enumerate() {
+            // each callback completes the other promise, racing its shutdown action
+            let other = slots[1 - i].clone();
+            let promise = crate::from_fn(
+                move |_: Result<u32, DropReason>| {
+                    if let Some(other) = other.lock().unwrap().take() {
+                        other.complete(Ok(0));
+                    }
+                },
+                Err,
+            );
+            *slot.lock().unwrap() = Some(scope.register(promise).unwrap().into());
+        }
+        assert_eq!(scope.shutdown(shutdown()), 1);
+    }
+
+    #[test]
+    fn rejects_registration_after_shutdown() {
+        let scope = PromiseScope::new();
+        scope.shutdown(shutdown());
+        assert!(scope.is_shut_down());
+        let (promise, recorder) = mock(Err);
+        let promise = scope.register(promise).unwrap_err();
+        promise.complete(Ok(1));
+        assert_completed_with(&recorder, Ok(1));
+    }
+}
@@ -0,0 +1,142 @@
+use std::sync::{Arc, Mutex};
+
+use crate::{DropReason, Promise};
+
+/// A clonable, thread-safe promise where the first completion wins
+///
+/// Any clone may attempt to complete the promise, but only one completion is delivered.
+/// If no clone completes it, the promise is dropped (and completed with its drop value)
+/// when the last clone is dropped.
+#[derive(Debug)]
+pub struct SharedPromise<T, V> {
+    inner: Arc<Mutex<Option<Promise<T, V>>>>,
+}
+
+impl<T, V> Clone for SharedPromise<T, V> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T, V> SharedPromise<T, V> {
+    /// Create a shared promise from a promise
+    pub fn new(promise: Promise<T, V>) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(Some(promise))),
+        }
+    }
+
+    /// Attempt to complete the promise, returning true if this caller won the race
+    pub fn try_complete(&self, value: V) -> bool {
+        match self.take() {
+            Some(promise) => {
+                promise.complete(value);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Attempt to complete the promise with the drop value for the specified reason,
+    /// returning true if this caller won the race
+    pub fn try_abandon(&self, reason: DropReason) -> bool {
+        match self.take() {
+            Some(promise) => {
+                promise.abandon(reason);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Attempt to complete the promise, returning the value if another caller already won the
+    /// race or the value could not be delivered
+    pub(crate) fn try_deliver(&self, value: V) -> Result<(), V> {
+        match self.take() {
+            Some(promise) => promise.try_complete(value),
+            None => Err(value),
+        }
+    }
+
+    /// Returns true if the promise has already been completed
+    pub fn is_completed(&self) -> bool {
+        self.inner.lock().unwrap().is_none()
+    }
+
+    fn take(&self) -> Option<Promise<T, V>> {
+        // the promise is completed outside the lock so that callbacks may use this promise
+        self.inner.lock().unwrap().take()
+    }
+}
+
+impl<T, V> From<Promise<T, V>> for SharedPromise<T, V> {
+    fn from(promise: Promise<T, V>) -> Self {
+        Self::new(promise)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+
+    #[test]
+    fn is_send_and_sync() {
+        fn assert_send_sync<X: Send + Sync>() {}
+        assert_send_sync::<
+            SharedPromise<MockCallback<Result<u32, DropReason>>, Result<u32, DropReason>>,
+        >();
+    }
+
+    #[test]
+    fn first_completer_wins() {
+        let (promise, recorder) = mock(Err);
+        let shared = SharedPromise::new(promise);
+        let other = shared.clone();
+        assert!(shared.try_complete(Ok(1)));
+        assert!(!other.try_complete(Ok(2)));
+        assert!(other.is_completed());
+        assert_completed_with(&recorder, Ok(1));
+    }
+
+    #[test]
+    fn exactly_one_thread_wins_race() {
+        let (promise, recorder) = mock(Err);
+        let shared = SharedPromise::from(promise);
+        let threads: Vec<_> = (0..8)
+            .map(|i| {
+                let shared = shared.clone();
+                std::thread::spawn(move || shared.try_complete(Ok(i)))
+            })
+            .collect();
+        let winners = threads
+            .into_iter()
+            .map(|t| t.join().unwrap())
+            .filter(|won| *won)
+            .count();
+        assert_eq!(winners, 1);
+        assert!(expect_exactly_once(&recorder).is_ok());
+    }
+
+    #[test]
+    fn last_clone_dropped_completes_with_drop_value() {
+        let (promise, recorder) = mock(Err::<u32, _>);
+        let shared = SharedPromise::new(promise);
+        let other = shared.clone();
+        drop(shared);
+        assert_not_yet_completed(&recorder);
+        drop(other);
+        assert_eq!(assert_dropped(&recorder), DropReason::Abandoned);
+    }
+
+    #[test]
+    fn try_abandon_forwards_reason() {
+        let (promise, recorder) = mock(Err);
+        let shared = SharedPromise::new(promise);
+        assert!(shared.try_abandon(DropReason::Cancelled));
+        assert!(!shared.try_complete(Ok(1)));
+        assert_eq!(assert_dropped(&recorder), DropReason::Cancelled);
+    }
+}
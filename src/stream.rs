@@ -0,0 +1,139 @@
+use std::marker::PhantomData;
+
+use crate::{DropReason, Promise};
+
+/// Types convertible to a StreamPromise must implement this type
+///
+/// The callback receives any number of items followed by exactly one terminal value.
+pub trait StreamFutureType<I, V> {
+    /// The terminal value that will be returned if the StreamPromise wrapping this instance is
+    /// dropped without being finished
+    fn drop_value(&self, reason: DropReason) -> V;
+
+    /// Deliver an item
+    fn next(&mut self, item: I);
+
+    /// Deliver the terminal value
+    fn finish(self, terminal: V);
+}
+
+/// A StreamPromise is guaranteed to deliver exactly one terminal value to its underlying
+/// StreamFutureType, even if it is dropped.
+#[derive(Debug)]
+pub struct StreamPromise<T, I, V> {
+    promise: Promise<T, V>,
+    _item: PhantomData<fn(I)>,
+}
+
+/// Wrap a type that implements StreamFutureType into a drop-safe stream promise
+#[track_caller]
+pub fn wrap_stream<T, I, V>(callback: T) -> StreamPromise<T, I, V>
+where
+    T: StreamFutureType<I, V>,
+{
+    StreamPromise {
+        promise: Promise::from_parts(
+            callback,
+            |cb, terminal| {
+                cb.finish(terminal);
+                Ok(())
+            },
+            |cb, reason| {
+                let terminal = cb.drop_value(reason);
+                cb.finish(terminal);
+            },
+        ),
+        _item: PhantomData,
+    }
+}
+
+impl<T, I, V> StreamPromise<T, I, V>
+where
+    T: StreamFutureType<I, V>,
+{
+    /// Deliver an item
+    pub fn next(&mut self, item: I) {
+        if let Some(cb) = self.promise.inner_mut() {
+            cb.next(item);
+        }
+    }
+
+    /// Deliver the terminal value, consuming the stream promise
+    pub fn finish(self, terminal: V) {
+        self.promise.complete(terminal);
+    }
+
+    /// Deliver the terminal drop value for the specified reason, consuming the stream promise
+    pub fn abandon(self, reason: DropReason) {
+        self.promise.abandon(reason);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    enum Event {
+        Item(u32),
+        Complete,
+        Error(DropReason),
+    }
+
+    struct Progress<'a> {
+        events: &'a mut Vec<Event>,
+    }
+
+    impl<'a> StreamFutureType<u32, Result<(), DropReason>> for Progress<'a> {
+        fn drop_value(&self, reason: DropReason) -> Result<(), DropReason> {
+            Err(reason)
+        }
+
+        fn next(&mut self, item: u32) {
+            self.events.push(Event::Item(item));
+        }
+
+        fn finish(self, terminal: Result<(), DropReason>) {
+            self.events.push(match terminal {
+                Ok(()) => Event::Complete,
+                Err(reason) => Event::Error(reason),
+            });
+        }
+    }
+
+    #[test]
+    fn delivers_items_then_terminal_value() {
+        let mut events = Vec::new();
+        let mut stream = wrap_stream(Progress {
+            events: &mut events,
+        });
+        stream.next(1);
+        stream.next(2);
+        stream.finish(Ok(()));
+        assert_eq!(events, [Event::Item(1), Event::Item(2), Event::Complete]);
+    }
+
+    #[test]
+    fn delivers_drop_value_when_dropped_halfway() {
+        let mut events = Vec::new();
+        let mut stream = wrap_stream(Progress {
+            events: &mut events,
+        });
+        stream.next(1);
+        drop(stream);
+        assert_eq!(
+            events,
+            [Event::Item(1), Event::Error(DropReason::Abandoned)]
+        );
+    }
+
+    #[test]
+    fn abandon_forwards_reason() {
+        let mut events = Vec::new();
+        wrap_stream(Progress {
+            events: &mut events,
+        })
+        .abandon(DropReason::Shutdown);
+        assert_eq!(events, [Event::Error(DropReason::Shutdown)]);
+    }
+}
@@ -0,0 +1,313 @@
+//! Helpers for verifying the drop-safety contract in crates that complete promises
+//!
+//! [mock] creates a promise whose completions are recorded, and the assertion helpers
+//! check how the promise was completed:
+//!
+//! ```
+//! use sfio_promise::testing::*;
+//! use sfio_promise::DropReason;
+//!
+//! let (promise, recorder) = mock(|reason: DropReason| Err::<u32, _>(reason));
+//! assert_not_yet_completed(&recorder);
+//! drop(promise);
+//! assert_eq!(assert_dropped(&recorder), DropReason::Abandoned);
+//! ```
+//!
+//! Code that accepts a callback rather than a [Promise] can be tested with a named
+//! [StatefulFutureType](crate::StatefulFutureType) declared by
+//! [mock_future_type](crate::mock_future_type):
+//!
+//! ```
+//! use sfio_promise::testing::*;
+//! use sfio_promise::{mock_future_type, wrap, DropReason};
+//!
+//! mock_future_type! {
+//!     struct MockRead: Result<u32, DropReason> = Err;
+//! }
+//!
+//! let (callback, recorder) = MockRead::new();
+//! wrap(callback).abandon(DropReason::Shutdown);
+//! assert_eq!(assert_dropped(&recorder), DropReason::Shutdown);
+//! ```
+
+use std::cell::Cell;
+use std::fmt::Debug;
+use std::sync::{Arc, Mutex};
+
+use crate::{DropReason, Promise};
+
+/// Declare a named [StatefulFutureType](crate::StatefulFutureType) that records its completions
+/// in a [Recorder]
+///
+/// The drop value is produced by the `fn(DropReason) -> V` after `=`. The declared type has a
+/// `new()` constructor returning the instance and the [Recorder] observing it.
+#[macro_export]
+macro_rules! mock_future_type {
+    ($(#[$meta:meta])* $vis:vis struct $name:ident: $value:ty = $on_drop:expr;) => {
+        $(#[$meta])*
+        #[derive(Debug)]
+        $vis struct $name($crate::testing::MockCallback<$value>);
+
+        impl $name {
+            /// Create an instance and the recorder observing it
+            $vis fn new() -> (Self, $crate::testing::Recorder<$value>) {
+                let (callback, recorder) = $crate::testing::MockCallback::new($on_drop);
+                (Self(callback), recorder)
+            }
+        }
+
+        impl $crate::StatefulFutureType<$value> for $name {
+            fn drop_value(&self, reason: $crate::DropReason) -> $value {
+                self.0.drop_value(reason)
+            }
+
+            fn deliver(self, result: $value) {
+                self.0.deliver(result)
+            }
+        }
+    };
+}
+
+/// The inner value of a promise created by [mock]
+///
+/// A generic type cannot implement [StatefulFutureType](crate::StatefulFutureType) alongside
+/// the blanket implementation for every [FutureType](crate::FutureType), so the methods are
+/// inherent here. [mock_future_type](crate::mock_future_type) declares an implementation for a
+/// specific value type.
+#[derive(Debug)]
+pub struct MockCallback<V> {
+    recorder: Recorder<V>,
+    on_drop: fn(DropReason) -> V,
+    drop_reason: Cell<Option<DropReason>>,
+}
+
+impl<V> MockCallback<V> {
+    /// Create a callback and the recorder observing it
+    pub fn new(on_drop: fn(DropReason) -> V) -> (Self, Recorder<V>) {
+        let recorder = Recorder::new();
+        let callback = Self {
+            recorder: recorder.clone(),
+            on_drop,
+            drop_reason: Cell::new(None),
+        };
+        (callback, recorder)
+    }
+
+    /// Produce the drop value for the reason, which is recorded by the next [Self::deliver]
+    pub fn drop_value(&self, reason: DropReason) -> V {
+        self.drop_reason.set(Some(reason));
+        (self.on_drop)(reason)
+    }
+
+    /// Record the value, and the drop reason if it was produced by [Self::drop_value]
+    pub fn deliver(self, result: V) {
+        if let Some(reason) = self.drop_reason.get() {
+            self.recorder.drop_reasons.lock().unwrap().push(reason);
+        }
+        self.recorder.push(result);
+    }
+}
+
+/// Shared record of the completions of a mock
+#[derive(Debug)]
+pub struct Recorder<V> {
+    completions: Arc<Mutex<Vec<V>>>,
+    drop_reasons: Arc<Mutex<Vec<DropReason>>>,
+}
+
+impl<V> Clone for Recorder<V> {
+    fn clone(&self) -> Self {
+        Self {
+            completions: self.completions.clone(),
+            drop_reasons: self.drop_reasons.clone(),
+        }
+    }
+}
+
+impl<V> Default for Recorder<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Create a promise whose completions are recorded
+///
+/// If the promise is dropped, the value produced by `on_drop` is recorded.
+#[track_caller]
+pub fn mock<V>(on_drop: fn(DropReason) -> V) -> (Promise<MockCallback<V>, V>, Recorder<V>) {
+    let (callback, recorder) = MockCallback::new(on_drop);
+    let promise = Promise::from_parts(
+        callback,
+        |callback, value| {
+            callback.deliver(value);
+            Ok(())
+        },
+        |callback, reason| {
+            let value = callback.drop_value(reason);
+            callback.deliver(value);
+        },
+    );
+    (promise, recorder)
+}
+
+impl<V> Recorder<V> {
+    /// Create a recorder that has not observed any completion
+    pub fn new() -> Self {
+        Self {
+            completions: Default::default(),
+            drop_reasons: Default::default(),
+        }
+    }
+
+    /// The list of values the promise was completed with
+    pub fn completions(&self) -> Arc<Mutex<Vec<V>>> {
+        self.completions.clone()
+    }
+
+    /// The reasons for every completion with a drop value
+    pub fn drop_reasons(&self) -> Vec<DropReason> {
+        self.drop_reasons.lock().unwrap().clone()
+    }
+
+    /// The number of times the promise was completed
+    pub fn count(&self) -> usize {
+        self.completions.lock().unwrap().len()
+    }
+
+    fn push(&self, value: V) {
+        self.completions.lock().unwrap().push(value);
+    }
+}
+
+/// Assert that the promise was completed exactly once and return the value
+#[track_caller]
+pub fn expect_exactly_once<V>(recorder: &Recorder<V>) -> V
+where
+    V: Clone + Debug,
+{
+    let completions = recorder.completions.lock().unwrap();
+    match completions.as_slice() {
+        [value] => value.clone(),
+        other => panic!("expected exactly one completion, but found: {other:?}"),
+    }
+}
+
+/// Assert that the promise was completed exactly once with the expected value, and not by
+/// being dropped
+#[track_caller]
+pub fn assert_completed_with<V>(recorder: &Recorder<V>, expected: V)
+where
+    V: Clone + Debug + PartialEq,
+{
+    let value = expect_exactly_once(recorder);
+    assert!(
+        recorder.drop_reasons().is_empty(),
+        "expected completion with {expected:?}, but the promise was dropped"
+    );
+    assert_eq!(value, expected);
+}
+
+/// Assert that the promise was completed exactly once with its drop value and return the reason
+#[track_caller]
+pub fn assert_dropped<V>(recorder: &Recorder<V>) -> DropReason
+where
+    V: Clone + Debug,
+{
+    let value = expect_exactly_once(recorder);
+    match recorder.drop_reasons().as_slice() {
+        [reason] => *reason,
+        _ => panic!("expected the promise to be dropped, but it was completed with {value:?}"),
+    }
+}
+
+/// Assert that the promise has not been completed yet
+#[track_caller]
+pub fn assert_not_yet_completed<V>(recorder: &Recorder<V>)
+where
+    V: Debug,
+{
+    let completions = recorder.completions.lock().unwrap();
+    assert!(
+        completions.is_empty(),
+        "expected no completion, but found: {:?}",
+        completions.as_slice()
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn on_drop(reason: DropReason) -> Result<u32, DropReason> {
+        Err(reason)
+    }
+
+    #[test]
+    fn records_completion() {
+        let (promise, recorder) = mock(on_drop);
+        assert_not_yet_completed(&recorder);
+        promise.complete(Ok(42));
+        assert_completed_with(&recorder, Ok(42));
+        assert_eq!(recorder.completions().lock().unwrap().as_slice(), [Ok(42)]);
+    }
+
+    #[test]
+    fn records_drop_reason() {
+        let (promise, recorder) = mock(on_drop);
+        promise.abandon(DropReason::Cancelled);
+        assert_eq!(assert_dropped(&recorder), DropReason::Cancelled);
+        assert_eq!(expect_exactly_once(&recorder), Err(DropReason::Cancelled));
+    }
+
+    crate::mock_future_type! {
+        struct MockRead: Result<u32, DropReason> = Err;
+    }
+
+    #[test]
+    fn declared_mock_is_a_stateful_future_type() {
+        fn start_read<T: crate::StatefulFutureType<Result<u32, DropReason>>>(callback: T) {
+            drop(crate::wrap(callback));
+        }
+
+        let (callback, recorder) = MockRead::new();
+        start_read(callback);
+        assert_eq!(assert_dropped(&recorder), DropReason::Abandoned);
+
+        let (callback, recorder) = MockRead::new();
+        crate::wrap(callback).complete(Ok(1));
+        assert_completed_with(&recorder, Ok(1));
+    }
+
+    #[test]
+    fn drop_reason_stays_with_its_instance() {
+        use crate::StatefulFutureType;
+
+        let (unused, _) = MockRead::new();
+        let _ = unused.drop_value(DropReason::Abandoned);
+        let (callback, recorder) = MockRead::new();
+        crate::wrap(callback).complete(Ok(1));
+        assert_completed_with(&recorder, Ok(1));
+
+        let (callback, recorder) = MockRead::new();
+        let value = callback.drop_value(DropReason::Cancelled);
+        std::thread::spawn(move || callback.deliver(value))
+            .join()
+            .unwrap();
+        assert_eq!(assert_dropped(&recorder), DropReason::Cancelled);
+    }
+
+    #[test]
+    #[should_panic(expected = "but the promise was dropped")]
+    fn assert_completed_with_rejects_drop() {
+        let (promise, recorder) = mock(|_| 0u32);
+        drop(promise);
+        assert_completed_with(&recorder, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "expected exactly one completion")]
+    fn expect_exactly_once_rejects_no_completion() {
+        let (_promise, recorder) = mock(|_| 0u32);
+        expect_exactly_once(&recorder);
+    }
+}
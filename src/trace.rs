@@ -0,0 +1,216 @@
+use std::panic::Location;
+use std::time::Instant;
+
+use crate::DropReason;
+
+/// Lifecycle instrumentation of a single promise
+#[derive(Debug)]
+pub(crate) struct Trace {
+    span: tracing::Span,
+    type_name: &'static str,
+    location: &'static Location<'static>,
+    created: Instant,
+}
+
+impl Trace {
+    pub(crate) fn new<T>(location: &'static Location<'static>) -> Self {
+        let type_name = std::any::type_name::<T>();
+        let span = tracing::debug_span!("promise", r#type = type_name, location = %location);
+        tracing::debug!(parent: &span, "promise created");
+        Self {
+            span,
+            type_name,
+            location,
+            created: Instant::now(),
+        }
+    }
+
+    pub(crate) fn completed(&self) {
+        tracing::debug!(
+            parent: &self.span,
+            elapsed_ms = self.created.elapsed().as_millis() as u64,
+            "promise completed"
+        );
+    }
+
+    pub(crate) fn abandoned(&self, reason: DropReason) {
+        tracing::info!(
+            parent: &self.span,
+            ?reason,
+            r#type = self.type_name,
+            elapsed_ms = self.created.elapsed().as_millis() as u64,
+            "promise abandoned"
+        );
+    }
+
+    pub(crate) fn settled(&self, reason: DropReason) {
+        tracing::debug!(
+            parent: &self.span,
+            ?reason,
+            elapsed_ms = self.created.elapsed().as_millis() as u64,
+            "promise already completed"
+        );
+    }
+
+    pub(crate) fn dropped(&self, reason: DropReason) {
+        tracing::warn!(
+            parent: &self.span,
+            ?reason,
+            r#type = self.type_name,
+            location = %self.location,
+            elapsed_ms = self.created.elapsed().as_millis() as u64,
+            "promise dropped without being completed"
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{wrap, CancellationToken, DropReason, FutureType};
+    use std::fmt::Debug;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::{Arc, Mutex};
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event, Level, Metadata, Subscriber};
+
+    struct Traced;
+
+    impl FutureType<u32> for Traced {
+        fn on_drop() -> u32 {
+            0
+        }
+
+        fn complete(self, _result: u32) {}
+    }
+
+    struct Recorder {
+        max_level: Level,
+        next_span: AtomicU64,
+        events: Mutex<Vec<(Level, String)>>,
+        types: Mutex<Vec<String>>,
+    }
+
+    #[derive(Default)]
+    struct Message {
+        message: String,
+        r#type: Option<String>,
+    }
+
+    impl Visit for Message {
+        fn record_str(&mut self, field: &Field, value: &str) {
+            if field.name() == "type" {
+                self.r#type = Some(value.to_string());
+            }
+        }
+
+        fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+            if field.name() == "message" {
+                self.message = format!("{value:?}");
+            }
+        }
+    }
+
+    impl Subscriber for Recorder {
+        fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+            *metadata.level() <= self.max_level
+        }
+
+        fn new_span(&self, _: &Attributes<'_>) -> Id {
+            Id::from_u64(self.next_span.fetch_add(1, Ordering::Relaxed) + 1)
+        }
+
+        fn record(&self, _: &Id, _: &Record<'_>) {}
+
+        fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+        fn event(&self, event: &Event<'_>) {
+            let mut message = Message::default();
+            event.record(&mut message);
+            self.types.lock().unwrap().extend(message.r#type);
+            self.events
+                .lock()
+                .unwrap()
+                .push((*event.metadata().level(), message.message));
+        }
+
+        fn enter(&self, _: &Id) {}
+
+        fn exit(&self, _: &Id) {}
+    }
+
+    fn record_at(max_level: Level, f: impl FnOnce()) -> Arc<Recorder> {
+        let recorder = Arc::new(Recorder {
+            max_level,
+            next_span: AtomicU64::new(0),
+            events: Mutex::default(),
+            types: Mutex::default(),
+        });
+        tracing::subscriber::with_default(recorder.clone(), f);
+        recorder
+    }
+
+    fn record(f: impl FnOnce()) -> Vec<(Level, String)> {
+        let recorder = record_at(Level::TRACE, f);
+        let events = recorder.events.lock().unwrap().clone();
+        events
+    }
+
+    #[test]
+    fn emits_events_for_completion() {
+        let events = record(|| wrap(Traced).complete(1));
+        assert_eq!(
+            events,
+            [
+                (Level::DEBUG, "promise created".to_string()),
+                (Level::DEBUG, "promise completed".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn warns_when_dropped() {
+        let events = record(|| drop(wrap(Traced)));
+        assert_eq!(
+            events.last(),
+            Some(&(
+                Level::WARN,
+                "promise dropped without being completed".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn reports_explicit_abandonment() {
+        let events = record(|| wrap(Traced).abandon(DropReason::Shutdown));
+        assert_eq!(
+            events.last(),
+            Some(&(Level::INFO, "promise abandoned".to_string()))
+        );
+    }
+
+    #[test]
+    fn does_not_warn_when_wrapped_promise_was_already_completed() {
+        let token = CancellationToken::new();
+        let events = record(|| {
+            let promise = wrap(Traced).with_cancellation(&token, 9);
+            token.cancel();
+            drop(promise);
+        });
+        assert!(!events.iter().any(|(level, _)| *level == Level::WARN));
+        assert_eq!(
+            events.last(),
+            Some(&(Level::DEBUG, "promise already completed".to_string()))
+        );
+    }
+
+    #[test]
+    fn reports_type_without_debug_span() {
+        let recorder = record_at(Level::INFO, || {
+            drop(wrap(Traced));
+            wrap(Traced).abandon(DropReason::Shutdown);
+        });
+        let name = std::any::type_name::<Traced>();
+        assert_eq!(recorder.types.lock().unwrap().as_slice(), [name, name]);
+    }
+}